@@ -0,0 +1,403 @@
+use std::cmp;
+use std::io::{Read, Seek, SeekFrom};
+
+use error;
+use metadata::Metadata;
+use reader::Reader;
+use slice_reader::SliceReader;
+use MainReader;
+
+const ZIP_END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY: u32 = 0x0606_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR: u32 = 0x0706_4b50;
+const ZIP_CENTRAL_DIRECTORY_HEADER: u32 = 0x0201_4b50;
+const ZIP_LOCAL_FILE_HEADER: u32 = 0x0403_4b50;
+const ZIP_STORED: u16 = 0;
+const TAR_BLOCK_SIZE: u64 = 512;
+/// Largest ZIP central directory loaded in memory.
+const MAX_ZIP_DIRECTORY_SIZE: u64 = 64 * 1024 * 1024;
+/// Largest TAR long name or PAX header loaded in memory.
+const MAX_TAR_HEADER_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+}
+
+/// Location of a member's data inside an archive.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ArchiveMember {
+    pub name: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// Split `archive.zip#path/inside.mxf` into the archive location, its kind and the member path.
+/// The archive may be a URL with a query string, e.g. a presigned URL.
+pub fn split_member(filename: &str) -> Option<(&str, ArchiveKind, &str)> {
+    let separator = filename.find('#')?;
+    let (archive, member) = (&filename[..separator], &filename[separator + 1..]);
+
+    let path = match archive.find('?') {
+        Some(query) => &archive[..query],
+        None => archive,
+    };
+    let lowercase_archive = path.to_lowercase();
+    let kind = if lowercase_archive.ends_with(".zip") {
+        ArchiveKind::Zip
+    } else if lowercase_archive.ends_with(".tar") {
+        ArchiveKind::Tar
+    } else {
+        return None;
+    };
+
+    if member.is_empty() {
+        return None;
+    }
+    Some((archive, kind, member))
+}
+
+pub fn exists(filename: &str) -> bool {
+    open_member(filename).is_ok()
+}
+
+pub fn stat(filename: &str) -> Result<Option<Metadata>, error::Error> {
+    stat_with(filename, MainReader::new())
+}
+
+/// Like `stat`, checking and opening the archive with `reader` and its settings.
+pub fn stat_with(filename: &str, mut reader: MainReader) -> Result<Option<Metadata>, error::Error> {
+    let (archive, kind, member) =
+        split_member(filename).ok_or_else(|| format!("Not an archive member: {}", filename))?;
+
+    if reader.stat(archive)?.is_none() {
+        return Ok(None);
+    }
+
+    reader.try_open(archive)?;
+
+    let archive_member = match kind {
+        ArchiveKind::Zip => find_zip_member(&mut reader, member)?,
+        ArchiveKind::Tar => find_tar_member(&mut reader, member)?,
+    };
+
+    Ok(archive_member.map(|archive_member| Metadata {
+        size: Some(archive_member.size),
+        ..Default::default()
+    }))
+}
+
+/// Open the archive through a `MainReader` and return a reader over the requested member.
+pub fn open_member(filename: &str) -> Result<SliceReader<MainReader>, String> {
+    open_member_with(filename, MainReader::new())
+}
+
+/// Like `open_member`, opening the archive with `reader` and its settings.
+pub fn open_member_with(
+    filename: &str,
+    mut reader: MainReader,
+) -> Result<SliceReader<MainReader>, String> {
+    let (archive, kind, member) =
+        split_member(filename).ok_or_else(|| format!("Not an archive member: {}", filename))?;
+
+    reader.open(archive)?;
+
+    let archive_member = match kind {
+        ArchiveKind::Zip => find_zip_member(&mut reader, member)?,
+        ArchiveKind::Tar => find_tar_member(&mut reader, member)?,
+    }
+    .ok_or_else(|| format!("No member {} in {}", member, archive))?;
+
+    Ok(SliceReader::create(
+        reader,
+        archive_member.offset,
+        archive_member.offset.saturating_add(archive_member.size),
+    ))
+}
+
+fn read_at<R: Read + Seek>(
+    reader: &mut R,
+    position: u64,
+    size: usize,
+) -> Result<Vec<u8>, error::Error> {
+    reader.seek(SeekFrom::Start(position))?;
+
+    let mut data = vec![0; size];
+    reader
+        .read_exact(&mut data)
+        .map_err(|error| match error::Error::from(error) {
+            error::Error::Io(error) => {
+                format!("Unable to read archive at {}: {}", position, error).into()
+            }
+            error => error,
+        })?;
+    Ok(data)
+}
+
+/// Read metadata of `length` bytes announced by the archive itself, after
+/// checking it against the archive size and `limit`.
+fn read_metadata<R: Read + Seek>(
+    reader: &mut R,
+    position: u64,
+    length: u64,
+    archive_size: u64,
+    limit: u64,
+) -> Result<Vec<u8>, error::Error> {
+    let end = position.checked_add(length);
+    if length > limit || end.map_or(true, |end| end > archive_size) {
+        return Err(format!(
+            "Invalid archive metadata of {} bytes at {}",
+            length, position
+        )
+        .into());
+    }
+    read_at(reader, position, length as usize)
+}
+
+fn le_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from(data[offset]) | u16::from(data[offset + 1]) << 8
+}
+
+fn le_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from(le_u16(data, offset)) | u32::from(le_u16(data, offset + 2)) << 16
+}
+
+fn le_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from(le_u32(data, offset)) | u64::from(le_u32(data, offset + 4)) << 32
+}
+
+/// Locate a stored member from the ZIP central directory, reading only the
+/// end of the archive, the central directory and the member local header.
+pub fn find_zip_member<R: Reader + Read + Seek>(
+    reader: &mut R,
+    name: &str,
+) -> Result<Option<ArchiveMember>, error::Error> {
+    let size = reader.get_size()?;
+    let tail_size = cmp::min(size, 22 + 65_535);
+    let tail_position = size - tail_size;
+    let tail = read_at(reader, tail_position, tail_size as usize)?;
+
+    let end_offset = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|offset| le_u32(&tail, *offset) == ZIP_END_OF_CENTRAL_DIRECTORY)
+        .ok_or_else(|| "Missing ZIP end of central directory".to_string())?;
+
+    let mut directory_size = u64::from(le_u32(&tail, end_offset + 12));
+    let mut directory_offset = u64::from(le_u32(&tail, end_offset + 16));
+
+    if directory_size == 0xffff_ffff || directory_offset == 0xffff_ffff {
+        if end_offset < 20
+            || le_u32(&tail, end_offset - 20) != ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR
+        {
+            return Err("Missing ZIP64 end of central directory locator"
+                .to_string()
+                .into());
+        }
+        let zip64_end_position = le_u64(&tail, end_offset - 20 + 8);
+        let zip64_end = read_metadata(reader, zip64_end_position, 56, size, 56)?;
+        if le_u32(&zip64_end, 0) != ZIP64_END_OF_CENTRAL_DIRECTORY {
+            return Err("Invalid ZIP64 end of central directory".to_string().into());
+        }
+        directory_size = le_u64(&zip64_end, 40);
+        directory_offset = le_u64(&zip64_end, 48);
+    }
+
+    let directory = read_metadata(
+        reader,
+        directory_offset,
+        directory_size,
+        size,
+        MAX_ZIP_DIRECTORY_SIZE,
+    )?;
+
+    let mut offset = 0;
+    while offset + 46 <= directory.len() {
+        if le_u32(&directory, offset) != ZIP_CENTRAL_DIRECTORY_HEADER {
+            return Err("Invalid ZIP central directory header".to_string().into());
+        }
+
+        let method = le_u16(&directory, offset + 10);
+        let mut compressed_size = u64::from(le_u32(&directory, offset + 20));
+        let mut uncompressed_size = u64::from(le_u32(&directory, offset + 24));
+        let name_length = le_u16(&directory, offset + 28) as usize;
+        let extra_length = le_u16(&directory, offset + 30) as usize;
+        let comment_length = le_u16(&directory, offset + 32) as usize;
+        let mut header_position = u64::from(le_u32(&directory, offset + 42));
+
+        let name_start = offset + 46;
+        let extra_start = name_start + name_length;
+        let next_offset = extra_start + extra_length + comment_length;
+        if next_offset > directory.len() {
+            return Err("Truncated ZIP central directory".to_string().into());
+        }
+
+        if &directory[name_start..extra_start] == name.as_bytes() {
+            let extra = &directory[extra_start..extra_start + extra_length];
+            let mut extra_offset = 0;
+            while extra_offset + 4 <= extra.len() {
+                let header_id = le_u16(extra, extra_offset);
+                let data_size = le_u16(extra, extra_offset + 2) as usize;
+                let mut field = extra_offset + 4;
+                if header_id == 0x0001 {
+                    if uncompressed_size == 0xffff_ffff && field + 8 <= extra.len() {
+                        uncompressed_size = le_u64(extra, field);
+                        field += 8;
+                    }
+                    if compressed_size == 0xffff_ffff && field + 8 <= extra.len() {
+                        compressed_size = le_u64(extra, field);
+                        field += 8;
+                    }
+                    if header_position == 0xffff_ffff && field + 8 <= extra.len() {
+                        header_position = le_u64(extra, field);
+                    }
+                }
+                extra_offset += 4 + data_size;
+            }
+
+            if method != ZIP_STORED {
+                return Err(format!(
+                    "ZIP member {} is compressed (method {}), only stored members are supported",
+                    name, method
+                )
+                .into());
+            }
+
+            let local_header = read_metadata(reader, header_position, 30, size, 30)?;
+            if le_u32(&local_header, 0) != ZIP_LOCAL_FILE_HEADER {
+                return Err("Invalid ZIP local file header".to_string().into());
+            }
+            let local_name_length = u64::from(le_u16(&local_header, 26));
+            let local_extra_length = u64::from(le_u16(&local_header, 28));
+
+            return Ok(Some(ArchiveMember {
+                name: name.to_string(),
+                offset: header_position + 30 + local_name_length + local_extra_length,
+                size: cmp::min(compressed_size, uncompressed_size),
+            }));
+        }
+
+        offset = next_offset;
+    }
+
+    Ok(None)
+}
+
+fn parse_tar_string(data: &[u8]) -> String {
+    let end = data
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+fn parse_tar_size(data: &[u8]) -> Result<u64, String> {
+    if data[0] & 0x80 != 0 {
+        let mut size = u64::from(data[0] & 0x7f);
+        for byte in &data[1..] {
+            size = (size << 8) | u64::from(*byte);
+        }
+        return Ok(size);
+    }
+
+    let octal = parse_tar_string(data);
+    let octal = octal.trim_matches(|c: char| c == ' ' || c == '\0');
+    if octal.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(octal, 8).map_err(|msg| format!("Invalid TAR size: {}", msg))
+}
+
+fn parse_pax_path(data: &[u8]) -> Option<String> {
+    let records = String::from_utf8_lossy(data);
+    for record in records.lines() {
+        if let Some(position) = record.find(" path=") {
+            return Some(record[position + 6..].to_string());
+        }
+    }
+    None
+}
+
+/// Member name without the `./` prefix written by `tar -C dir .`.
+fn tar_name(name: &str) -> &str {
+    name.trim_start_matches("./")
+}
+
+/// Locate a member by walking the TAR headers, skipping over member data.
+pub fn find_tar_member<R: Reader + Read + Seek>(
+    reader: &mut R,
+    name: &str,
+) -> Result<Option<ArchiveMember>, error::Error> {
+    let size = reader.get_size()?;
+    let mut position = 0;
+    let mut long_name: Option<String> = None;
+
+    while position + TAR_BLOCK_SIZE <= size {
+        let header = read_at(reader, position, TAR_BLOCK_SIZE as usize)?;
+        if header.iter().all(|byte| *byte == 0) {
+            break;
+        }
+
+        let member_size = parse_tar_size(&header[124..136])?;
+        let type_flag = header[156];
+        let data_position = position + TAR_BLOCK_SIZE;
+        let padding = (TAR_BLOCK_SIZE - member_size % TAR_BLOCK_SIZE) % TAR_BLOCK_SIZE;
+        let next_position = data_position
+            .saturating_add(member_size)
+            .saturating_add(padding);
+
+        match type_flag {
+            b'L' => {
+                let data = read_metadata(
+                    reader,
+                    data_position,
+                    member_size,
+                    size,
+                    MAX_TAR_HEADER_SIZE,
+                )?;
+                long_name = Some(parse_tar_string(&data));
+            }
+            b'x' => {
+                let data = read_metadata(
+                    reader,
+                    data_position,
+                    member_size,
+                    size,
+                    MAX_TAR_HEADER_SIZE,
+                )?;
+                long_name = parse_pax_path(&data);
+            }
+            _ => {
+                let member_name = match long_name.take() {
+                    Some(long_name) => long_name,
+                    None => {
+                        let short_name = parse_tar_string(&header[0..100]);
+                        let prefix = if &header[257..262] == b"ustar" {
+                            parse_tar_string(&header[345..500])
+                        } else {
+                            "".to_string()
+                        };
+                        if prefix.is_empty() {
+                            short_name
+                        } else {
+                            format!("{}/{}", prefix, short_name)
+                        }
+                    }
+                };
+
+                if tar_name(&member_name) == tar_name(name) && (type_flag == b'0' || type_flag == 0)
+                {
+                    return Ok(Some(ArchiveMember {
+                        name: member_name,
+                        offset: data_position,
+                        size: member_size,
+                    }));
+                }
+            }
+        }
+
+        position = next_position;
+    }
+
+    Ok(None)
+}
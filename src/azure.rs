@@ -0,0 +1,247 @@
+use base64;
+use hmac::{Hmac, Mac};
+use httpdate;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, RANGE};
+use reqwest::Url;
+use sha2::Sha256;
+
+use http_reader;
+use listing::{relative_name, xml_element, xml_elements, xml_unescape, Entry};
+use signer::{encode_path, to_header_value, Signer};
+
+use std::env;
+use std::time::SystemTime;
+
+const AZURE_STORAGE_VERSION: &str = "2019-02-02";
+
+#[derive(Clone, Debug)]
+pub enum AzureAccess {
+    Anonymous,
+    SharedKey(Vec<u8>),
+    Sas(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct AzureCredentials {
+    pub account: String,
+    pub endpoint: String,
+    pub access: AzureAccess,
+}
+
+impl AzureCredentials {
+    /// Build credentials from `AZURE_STORAGE_ACCOUNT`, `AZURE_STORAGE_KEY` or
+    /// `AZURE_STORAGE_SAS_TOKEN`, and optionally `AZURE_STORAGE_ENDPOINT`
+    /// (e.g. `http://127.0.0.1:10000/devstoreaccount1` for Azurite).
+    pub fn from_env() -> Result<AzureCredentials, String> {
+        let account = env::var("AZURE_STORAGE_ACCOUNT")
+            .map_err(|_| "Missing AZURE_STORAGE_ACCOUNT".to_string())?;
+
+        let endpoint = env::var("AZURE_STORAGE_ENDPOINT")
+            .unwrap_or_else(|_| format!("https://{}.blob.core.windows.net", account));
+
+        let access = if let Ok(key) = env::var("AZURE_STORAGE_KEY") {
+            let key = base64::decode(&key)
+                .map_err(|msg| format!("Invalid AZURE_STORAGE_KEY: {}", msg))?;
+            AzureAccess::SharedKey(key)
+        } else if let Ok(token) = env::var("AZURE_STORAGE_SAS_TOKEN") {
+            AzureAccess::Sas(token.trim_start_matches('?').to_string())
+        } else {
+            AzureAccess::Anonymous
+        };
+
+        Ok(AzureCredentials {
+            account,
+            endpoint,
+            access,
+        })
+    }
+
+    /// Translate `az://container/blob` into the blob HTTP URL.
+    pub fn blob_url(&self, filename: &str) -> Result<String, String> {
+        let path = filename.trim_start_matches("az://").trim_start_matches('/');
+
+        if !path.contains('/') {
+            return Err(format!("Missing blob name in {}", filename));
+        }
+
+        let url = format!(
+            "{}/{}",
+            self.endpoint.trim_end_matches('/'),
+            encode_path(path)
+        );
+        match self.access {
+            AzureAccess::Sas(ref token) => Ok(format!("{}?{}", url, token)),
+            _ => Ok(url),
+        }
+    }
+
+    /// List blobs and virtual directories under an `az://container/prefix` URL.
+    pub fn list(&self, filename: &str) -> Result<Vec<Entry>, String> {
+        let path = filename.trim_start_matches("az://").trim_start_matches('/');
+        let (container, prefix) = match path.find('/') {
+            Some(separator) => (&path[..separator], &path[separator + 1..]),
+            None => (path, ""),
+        };
+        let prefix = if prefix.is_empty() || prefix.ends_with('/') {
+            prefix.to_string()
+        } else {
+            format!("{}/", prefix)
+        };
+
+        let signer = Some(Signer::Azure(self.clone()));
+        let mut entries = vec![];
+        let mut marker = "".to_string();
+
+        loop {
+            let mut url = Url::parse(&format!(
+                "{}/{}",
+                self.endpoint.trim_end_matches('/'),
+                container
+            ))
+            .map_err(|msg| msg.to_string())?;
+            url.query_pairs_mut()
+                .append_pair("restype", "container")
+                .append_pair("comp", "list")
+                .append_pair("delimiter", "/")
+                .append_pair("prefix", &prefix);
+            if !marker.is_empty() {
+                url.query_pairs_mut().append_pair("marker", &marker);
+            }
+
+            let url = match self.access {
+                AzureAccess::Sas(ref token) => format!("{}&{}", url, token),
+                _ => url.to_string(),
+            };
+
+            let body = http_reader::get_text(&url, &signer)?;
+            let (mut page, next_marker) = parse_blob_list(&body, &prefix);
+            entries.append(&mut page);
+
+            match next_marker {
+                Some(next_marker) => marker = next_marker,
+                None => break,
+            }
+        }
+
+        Ok(entries)
+    }
+
+    pub fn sign(&self, method: &str, url: &str, headers: &mut HeaderMap) -> Result<(), String> {
+        let key = match self.access {
+            AzureAccess::SharedKey(ref key) => key,
+            _ => return Ok(()),
+        };
+
+        if !headers.contains_key("x-ms-date") {
+            let date = httpdate::fmt_http_date(SystemTime::now());
+            headers.insert("x-ms-date", to_header_value(&date)?);
+        }
+        if !headers.contains_key("x-ms-version") {
+            headers.insert(
+                "x-ms-version",
+                HeaderValue::from_static(AZURE_STORAGE_VERSION),
+            );
+        }
+
+        let string_to_sign = self.string_to_sign(method, url, headers)?;
+
+        let mut mac =
+            Hmac::<Sha256>::new_varkey(key).map_err(|_| "Invalid Azure shared key".to_string())?;
+        mac.input(string_to_sign.as_bytes());
+        let signature = base64::encode(&mac.result().code());
+
+        let authorization = format!("SharedKey {}:{}", self.account, signature);
+        headers.insert(AUTHORIZATION, to_header_value(&authorization)?);
+        Ok(())
+    }
+
+    fn string_to_sign(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &HeaderMap,
+    ) -> Result<String, String> {
+        let url = Url::parse(url).map_err(|msg| msg.to_string())?;
+
+        let range = match headers.get(RANGE) {
+            Some(value) => value.to_str().map_err(|msg| msg.to_string())?,
+            None => "",
+        };
+
+        let mut canonicalized_headers: Vec<(String, String)> = vec![];
+        for (name, value) in headers.iter() {
+            if name.as_str().starts_with("x-ms-") {
+                let value = value.to_str().map_err(|msg| msg.to_string())?;
+                canonicalized_headers.push((name.as_str().to_string(), value.trim().to_string()));
+            }
+        }
+        canonicalized_headers.sort();
+
+        let mut canonicalized_resource = format!("/{}{}", self.account, url.path());
+        let mut query: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| (name.to_lowercase(), value.into_owned()))
+            .collect();
+        query.sort();
+        for (name, value) in query {
+            canonicalized_resource += &format!("\n{}:{}", name, value);
+        }
+
+        let mut string_to_sign = format!("{}\n\n\n\n\n\n\n\n\n\n\n{}\n", method, range);
+        for (name, value) in canonicalized_headers {
+            string_to_sign += &format!("{}:{}\n", name, value);
+        }
+        string_to_sign += &canonicalized_resource;
+        Ok(string_to_sign)
+    }
+}
+
+/// Resolve an `az://container/blob` URL using credentials from the environment.
+pub fn resolve(filename: &str) -> Result<(String, Signer), String> {
+    let credentials = AzureCredentials::from_env()?;
+    let url = credentials.blob_url(filename)?;
+    Ok((url, Signer::Azure(credentials)))
+}
+
+pub fn list(filename: &str) -> Result<Vec<Entry>, String> {
+    AzureCredentials::from_env()?.list(filename)
+}
+
+/// Parse a List Blobs response page, returning entries relative to `prefix` and the next marker.
+pub fn parse_blob_list(body: &str, prefix: &str) -> (Vec<Entry>, Option<String>) {
+    let mut entries = vec![];
+
+    for blob in xml_elements(body, "Blob") {
+        let name = match xml_element(blob, "Name") {
+            Some(name) => xml_unescape(name),
+            None => continue,
+        };
+        entries.push(Entry {
+            name: relative_name(&name, prefix).to_string(),
+            size: xml_element(blob, "Content-Length").and_then(|size| size.parse().ok()),
+            modified: xml_element(blob, "Last-Modified")
+                .and_then(|date| httpdate::parse_http_date(date).ok()),
+            is_dir: false,
+        });
+    }
+
+    for blob_prefix in xml_elements(body, "BlobPrefix") {
+        if let Some(name) = xml_element(blob_prefix, "Name") {
+            let name = xml_unescape(name);
+            entries.push(Entry {
+                name: relative_name(&name, prefix)
+                    .trim_end_matches('/')
+                    .to_string(),
+                size: None,
+                modified: None,
+                is_dir: true,
+            });
+        }
+    }
+
+    let next_marker = xml_element(body, "NextMarker")
+        .map(|marker| marker.trim().to_string())
+        .filter(|marker| !marker.is_empty());
+
+    (entries, next_marker)
+}
@@ -1,9 +1,15 @@
+use std::cmp;
+
+/// Cache of fetched data, read through a cursor so that small reads do not
+/// move the remaining data.
 #[derive(Debug)]
 pub struct Buffer {
     pub size: Option<usize>,
     pub position: u64,
     pub max_end_position: Option<u64>,
-    pub buffer: Vec<u8>,
+    buffer: Vec<u8>,
+    /// Index in `buffer` of the next byte to deliver.
+    offset: usize,
 }
 
 impl Buffer {
@@ -13,31 +19,55 @@ impl Buffer {
             position: 0,
             max_end_position: None,
             buffer: Vec::new(),
+            offset: 0,
         }
     }
 
+    /// Index in `buffer` of the next byte to deliver.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
     pub fn get_cached_size(&self) -> usize {
-        self.buffer.len()
+        self.buffer.len() - self.offset
+    }
+
+    /// Data not delivered yet, without copying it.
+    pub fn get_cached_data(&self) -> &[u8] {
+        &self.buffer[self.offset..]
     }
 
     pub fn get_data(&mut self, buf: &mut [u8]) -> bool {
-        if buf.len() > self.buffer.len() {
+        if buf.len() > self.get_cached_size() {
             return false;
         }
-        let next_data = self.buffer.split_off(buf.len());
-        buf.clone_from_slice(&self.buffer);
-        self.buffer = next_data;
+        buf.clone_from_slice(&self.buffer[self.offset..self.offset + buf.len()]);
+        self.consume(buf.len());
 
-        debug!("left #{:?}", self.buffer.len());
+        debug!("left #{:?}", self.get_cached_size());
         true
     }
 
+    /// Skip `amount` cached bytes.
+    pub fn consume(&mut self, amount: usize) {
+        self.offset = cmp::min(self.offset + amount, self.buffer.len());
+        if self.offset == self.buffer.len() {
+            self.buffer.clear();
+            self.offset = 0;
+        }
+    }
+
     pub fn append_data(&mut self, full_data: &[u8]) {
+        if self.offset > 0 {
+            self.buffer.drain(..self.offset);
+            self.offset = 0;
+        }
         self.buffer.extend_from_slice(full_data);
     }
 
     pub fn reset(&mut self) {
         self.buffer = vec![];
+        self.offset = 0;
         self.position = 0;
     }
 }
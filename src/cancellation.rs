@@ -0,0 +1,70 @@
+use error::Error;
+
+use std::io;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Handle shared between a reader and other threads to abort its reads.
+///
+/// Once cancelled, `read` and `seek` fail with an error wrapping
+/// `Error::Cancelled`. The kind is not `Interrupted`, as `read_exact` and
+/// `read_to_end` silently retry on it.
+///
+/// An HTTP request in flight is abandoned at once, its connection being
+/// closed by a helper thread within the read timeout of the reader.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    state: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Abort the reads and wake up the waits of every clone.
+    pub fn cancel(&self) {
+        let (ref cancelled, ref condvar) = *self.state;
+        *cancelled.lock().unwrap() = true;
+        condvar.notify_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.state.0.lock().unwrap()
+    }
+
+    pub fn check(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Sleep for `duration`, failing as soon as the token is cancelled.
+    pub fn sleep(&self, duration: Duration) -> Result<(), Error> {
+        let end = Instant::now() + duration;
+        let (ref cancelled, ref condvar) = *self.state;
+        let mut is_cancelled = cancelled.lock().unwrap();
+        loop {
+            if *is_cancelled {
+                return Err(Error::Cancelled);
+            }
+            let now = Instant::now();
+            if now >= end {
+                return Ok(());
+            }
+            is_cancelled = condvar.wait_timeout(is_cancelled, end - now).unwrap().0;
+        }
+    }
+}
+
+/// Whether `error` was returned because the operation was cancelled.
+pub fn is_cancelled_error(error: &io::Error) -> bool {
+    match error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<Error>())
+    {
+        Some(Error::Cancelled) => true,
+        _ => false,
+    }
+}
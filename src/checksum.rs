@@ -0,0 +1,206 @@
+use base64;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use twox_hash::XxHash64;
+
+use error::Error;
+
+use std::fmt;
+use std::hash::Hasher as StdHasher;
+use std::io::{self, Read, Seek, SeekFrom};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    XxHash64,
+}
+
+/// Digest of some content, the value being lowercase hexadecimal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checksum {
+    pub algorithm: Algorithm,
+    pub value: String,
+}
+
+impl Checksum {
+    pub fn new(algorithm: Algorithm, value: &str) -> Checksum {
+        Checksum {
+            algorithm,
+            value: value.to_lowercase(),
+        }
+    }
+
+    /// Decode a base64 digest, as sent in `Content-MD5` or `Digest` headers.
+    pub fn from_base64(algorithm: Algorithm, value: &str) -> Option<Checksum> {
+        let digest = base64::decode(value.trim()).ok()?;
+        Some(Checksum::new(algorithm, &to_hex(&digest)))
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}:{}", self.algorithm, self.value)
+    }
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[derive(Clone)]
+enum Hasher {
+    Md5(Md5),
+    Sha1(Sha1),
+    Sha256(Sha256),
+    XxHash64(XxHash64),
+}
+
+impl Hasher {
+    fn create(algorithm: Algorithm) -> Hasher {
+        match algorithm {
+            Algorithm::Md5 => Hasher::Md5(Md5::new()),
+            Algorithm::Sha1 => Hasher::Sha1(Sha1::new()),
+            Algorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            Algorithm::XxHash64 => Hasher::XxHash64(XxHash64::with_seed(0)),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match *self {
+            Hasher::Md5(ref mut hasher) => hasher.input(data),
+            Hasher::Sha1(ref mut hasher) => hasher.input(data),
+            Hasher::Sha256(ref mut hasher) => hasher.input(data),
+            Hasher::XxHash64(ref mut hasher) => hasher.write(data),
+        }
+    }
+
+    fn checksum(&self) -> Checksum {
+        match self.clone() {
+            Hasher::Md5(hasher) => Checksum::new(Algorithm::Md5, &to_hex(&hasher.result())),
+            Hasher::Sha1(hasher) => Checksum::new(Algorithm::Sha1, &to_hex(&hasher.result())),
+            Hasher::Sha256(hasher) => Checksum::new(Algorithm::Sha256, &to_hex(&hasher.result())),
+            Hasher::XxHash64(hasher) => {
+                Checksum::new(Algorithm::XxHash64, &format!("{:016x}", hasher.finish()))
+            }
+        }
+    }
+}
+
+/// Digests of `data` for every algorithm.
+pub fn compute(data: &[u8], algorithms: &[Algorithm]) -> Vec<Checksum> {
+    algorithms
+        .iter()
+        .map(|algorithm| {
+            let mut hasher = Hasher::create(*algorithm);
+            hasher.update(data);
+            hasher.checksum()
+        })
+        .collect()
+}
+
+/// Compute digests of the data read through another reader.
+///
+/// Seeking is allowed: data read again is not hashed twice, but skipping
+/// data leaves the digests incomplete.
+pub struct ChecksumReader<R> {
+    pub reader: R,
+    pub position: u64,
+    /// Number of bytes hashed from the beginning of the stream.
+    pub hashed_size: u64,
+    hashers: Vec<Hasher>,
+    skipped: bool,
+}
+
+impl<R> fmt::Debug for ChecksumReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChecksumReader")
+            .field("position", &self.position)
+            .field("hashed_size", &self.hashed_size)
+            .finish()
+    }
+}
+
+impl<R: Read> ChecksumReader<R> {
+    pub fn create(reader: R, algorithms: &[Algorithm]) -> ChecksumReader<R> {
+        ChecksumReader {
+            reader,
+            position: 0,
+            hashed_size: 0,
+            hashers: algorithms
+                .iter()
+                .map(|algorithm| Hasher::create(*algorithm))
+                .collect(),
+            skipped: false,
+        }
+    }
+
+    /// Digests of the data hashed so far, failing when some data was skipped.
+    pub fn checksums(&self) -> Result<Vec<Checksum>, Error> {
+        if self.skipped {
+            return Err(Error::Other(format!(
+                "checksum incomplete, data skipped after {} bytes",
+                self.hashed_size
+            )));
+        }
+        Ok(self
+            .hashers
+            .iter()
+            .map(|hasher| hasher.checksum())
+            .collect())
+    }
+
+    /// Compare the digest computed with the same algorithm as `expected`.
+    pub fn verify(&self, expected: &Checksum) -> Result<(), Error> {
+        verify(&self.checksums()?, expected)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Compare `expected` with the checksum of the same algorithm in `checksums`.
+pub fn verify(checksums: &[Checksum], expected: &Checksum) -> Result<(), Error> {
+    let actual = checksums
+        .iter()
+        .find(|checksum| checksum.algorithm == expected.algorithm)
+        .ok_or_else(|| Error::Other(format!("no {:?} checksum computed", expected.algorithm)))?;
+
+    if actual.value != expected.value.to_lowercase() {
+        return Err(Error::ChecksumMismatch {
+            expected: expected.clone(),
+            actual: actual.clone(),
+        });
+    }
+    Ok(())
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let readed_size = self.reader.read(buf)?;
+        let end = self.position + readed_size as u64;
+
+        if self.position > self.hashed_size {
+            self.skipped = self.skipped || readed_size > 0;
+        } else if end > self.hashed_size {
+            let start = (self.hashed_size - self.position) as usize;
+            for hasher in &mut self.hashers {
+                hasher.update(&buf[start..readed_size]);
+            }
+            self.hashed_size = end;
+        }
+
+        self.position = end;
+        Ok(readed_size)
+    }
+}
+
+impl<R: Read + Seek> Seek for ChecksumReader<R> {
+    fn seek(&mut self, seek_from: SeekFrom) -> io::Result<u64> {
+        self.position = self.reader.seek(seek_from)?;
+        Ok(self.position)
+    }
+}
@@ -0,0 +1,334 @@
+use std::cmp;
+use std::io::{BufRead, Error, ErrorKind, Read, Seek, SeekFrom};
+
+use error;
+use metadata::Metadata;
+use reader::{ReadAt, Reader};
+use stats::TransferStats;
+use MainReader;
+
+/// Split a `concat:first|second|...` URL into its segment locations.
+pub fn parse_segments(filename: &str) -> Vec<String> {
+    filename
+        .trim_start_matches("concat:")
+        .split('|')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.to_string())
+        .collect()
+}
+
+/// Split `file.part001` into `file.part` and the part number with its width.
+fn split_part_number(filename: &str) -> Option<(&str, u64, usize)> {
+    let index = filename.rfind(".part")? + ".part".len();
+    let digits = &filename[index..];
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let number = digits.parse().ok()?;
+    Some((&filename[..index], number, digits.len()))
+}
+
+/// List `first` and the consecutive numbered parts following it
+/// (`file.part002`, `file.part003`, ...) until one does not exist.
+pub fn discover_parts(first: &str) -> Vec<String> {
+    let mut parts = vec![first.to_string()];
+    if let Some((prefix, number, width)) = split_part_number(first) {
+        for next in number + 1.. {
+            let part = format!("{}{:0width$}", prefix, next, width = width);
+            if !::exists(&part) {
+                break;
+            }
+            parts.push(part);
+        }
+    }
+    parts
+}
+
+/// Segment locations of a `concat:` URL. A single numbered part expands to
+/// all the parts following it.
+pub fn resolve_segments(filename: &str) -> Vec<String> {
+    let segments = parse_segments(filename);
+    if segments.len() == 1 {
+        return discover_parts(&segments[0]);
+    }
+    segments
+}
+
+pub fn exists(filename: &str) -> bool {
+    let segments = parse_segments(filename);
+    !segments.is_empty() && segments.iter().all(|segment| ::exists(segment))
+}
+
+/// Stat every segment; the concatenation exists only if all of them do.
+pub fn stat(filename: &str) -> Result<Option<Metadata>, error::Error> {
+    stat_with(filename, &MainReader::new())
+}
+
+/// Like `stat`, checking the segments with the settings of `reader`.
+pub fn stat_with(filename: &str, reader: &MainReader) -> Result<Option<Metadata>, error::Error> {
+    let segments = resolve_segments(filename);
+    if segments.is_empty() {
+        return Ok(None);
+    }
+
+    let mut size = Some(0);
+    for segment in segments {
+        match reader.stat(&segment)? {
+            Some(metadata) => {
+                size = match (size, metadata.size) {
+                    (Some(total), Some(segment_size)) => Some(total + segment_size),
+                    _ => None,
+                };
+            }
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(Metadata {
+        size,
+        ..Default::default()
+    }))
+}
+
+#[derive(Debug)]
+pub struct ConcatReader {
+    pub filenames: Vec<String>,
+    pub readers: Vec<MainReader>,
+    pub sizes: Vec<u64>,
+    /// Position of each segment start in the stream, followed by the total size.
+    pub offsets: Vec<u64>,
+    pub segment: usize,
+    pub position: u64,
+    pub cache_size: Option<usize>,
+    pub max_end_position: Option<u64>,
+}
+
+impl ConcatReader {
+    /// Open every segment in order and compute the total size from their sizes.
+    pub fn open_segments(&mut self, filenames: &[String]) -> Result<(), String> {
+        self.open_segments_with(filenames, MainReader::new)
+    }
+
+    /// Like `open_segments`, opening each segment with a reader from `new_reader`.
+    pub fn open_segments_with<F>(
+        &mut self,
+        filenames: &[String],
+        new_reader: F,
+    ) -> Result<(), String>
+    where
+        F: Fn() -> MainReader,
+    {
+        if filenames.is_empty() {
+            return Err("No segment to concatenate".to_string());
+        }
+
+        let mut readers = vec![];
+        let mut sizes = vec![];
+        for filename in filenames {
+            let mut reader = new_reader();
+            reader.open(filename)?;
+            reader.set_cache_size(self.cache_size);
+            sizes.push(reader.get_size()?);
+            readers.push(reader);
+        }
+
+        let mut offsets = vec![0];
+        for size in &sizes {
+            let end = offsets[offsets.len() - 1] + size;
+            offsets.push(end);
+        }
+
+        self.filenames = filenames.to_vec();
+        self.readers = readers;
+        self.sizes = sizes;
+        self.offsets = offsets;
+        self.segment = 0;
+        self.position = 0;
+        Ok(())
+    }
+
+    /// Transfer counters summed over the segments.
+    pub fn get_stats(&self) -> TransferStats {
+        let mut stats = TransferStats::default();
+        for reader in &self.readers {
+            if let Some(segment_stats) = reader.get_stats() {
+                stats.merge(&segment_stats);
+            }
+        }
+        stats
+    }
+
+    fn total_size(&self) -> u64 {
+        self.offsets.last().cloned().unwrap_or(0)
+    }
+
+    /// Last segment starting at or before `position`.
+    fn find_segment(&self, position: u64) -> usize {
+        let starts = &self.offsets[..self.readers.len()];
+        match starts.binary_search_by(|start| {
+            if *start <= position {
+                cmp::Ordering::Less
+            } else {
+                cmp::Ordering::Greater
+            }
+        }) {
+            Ok(index) | Err(index) => index.saturating_sub(1),
+        }
+    }
+
+    fn end_position(&self) -> u64 {
+        let size = self.total_size();
+        match self.max_end_position {
+            Some(max) => cmp::min(size, max.saturating_add(1)),
+            None => size,
+        }
+    }
+}
+
+impl Reader for ConcatReader {
+    fn new() -> ConcatReader {
+        ConcatReader {
+            filenames: vec![],
+            readers: vec![],
+            sizes: vec![],
+            offsets: vec![],
+            segment: 0,
+            position: 0,
+            cache_size: None,
+            max_end_position: None,
+        }
+    }
+
+    fn open(&mut self, filename: &str) -> Result<(), String> {
+        self.open_segments(&resolve_segments(filename))
+    }
+
+    fn get_position(&mut self) -> Result<u64, String> {
+        Ok(self.position)
+    }
+
+    fn get_cache_size(&self) -> Option<usize> {
+        self.cache_size
+    }
+
+    fn set_cache_size(&mut self, cache_size: Option<usize>) {
+        self.cache_size = cache_size;
+        for reader in &mut self.readers {
+            reader.set_cache_size(cache_size);
+        }
+    }
+
+    fn get_max_end_position(&self) -> Option<u64> {
+        self.max_end_position
+    }
+
+    fn set_max_end_position(&mut self, max_end_position: Option<u64>) {
+        self.max_end_position = max_end_position;
+    }
+
+    fn get_size(&mut self) -> Result<u64, String> {
+        if self.readers.is_empty() {
+            return Err("No segment opened".to_string());
+        }
+        Ok(self.total_size())
+    }
+}
+
+impl Read for ConcatReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let end_position = self.end_position();
+        if self.position >= end_position || buf.is_empty() {
+            return Ok(0);
+        }
+
+        while self.segment < self.readers.len() {
+            // An HTTP segment without cache returns nothing when asked for
+            // more bytes than it holds, so stop at the segment end.
+            let segment_end = cmp::min(self.offsets[self.segment + 1], end_position);
+            if self.position < segment_end {
+                let size = cmp::min(buf.len() as u64, segment_end - self.position) as usize;
+                let readed_size = self.readers[self.segment].read(&mut buf[..size])?;
+                if readed_size > 0 {
+                    self.position += readed_size as u64;
+                    return Ok(readed_size);
+                }
+            }
+
+            self.segment += 1;
+            if let Some(reader) = self.readers.get_mut(self.segment) {
+                reader.seek(SeekFrom::Start(0))?;
+            }
+        }
+        Ok(0)
+    }
+}
+
+impl BufRead for ConcatReader {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        let end_position = self.end_position();
+        if self.position >= end_position {
+            return Ok(&[]);
+        }
+
+        while self.segment + 1 < self.readers.len()
+            && self.readers[self.segment].fill_buf()?.is_empty()
+        {
+            self.segment += 1;
+            self.readers[self.segment].seek(SeekFrom::Start(0))?;
+        }
+
+        let size = (end_position - self.position) as usize;
+        match self.readers.get_mut(self.segment) {
+            Some(reader) => {
+                let data = reader.fill_buf()?;
+                Ok(&data[..cmp::min(size, data.len())])
+            }
+            None => Ok(&[]),
+        }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        if let Some(reader) = self.readers.get_mut(self.segment) {
+            reader.consume(amount);
+            self.position += amount as u64;
+        }
+    }
+}
+
+impl ReadAt for ConcatReader {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        if self.readers.is_empty() || offset >= self.total_size() {
+            return Ok(0);
+        }
+        let segment = self.find_segment(offset);
+        self.readers[segment].read_at(offset - self.offsets[segment], buf)
+    }
+}
+
+impl Seek for ConcatReader {
+    fn seek(&mut self, seek_from: SeekFrom) -> Result<u64, Error> {
+        if self.readers.is_empty() {
+            return Err(Error::new(ErrorKind::Other, "No segment opened"));
+        }
+
+        let position = match seek_from {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+            SeekFrom::End(offset) => self.total_size() as i64 + offset,
+        };
+        if position < 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        }
+        let position = position as u64;
+
+        let segment = self.find_segment(position);
+        let offset = position - self.offsets[segment];
+        self.readers[segment].seek(SeekFrom::Start(offset))?;
+        self.segment = segment;
+        self.position = position;
+        Ok(position)
+    }
+}
@@ -0,0 +1,137 @@
+use base64;
+use percent_encoding::percent_decode;
+
+use memory_reader::MemoryReader;
+use reader::{ReadAt, Reader};
+
+use std::io::{BufRead, Error, Read, Seek, SeekFrom};
+
+const DEFAULT_MEDIA_TYPE: &str = "text/plain;charset=US-ASCII";
+
+/// Decode a RFC 2397 `data:` URL into its media type and payload.
+pub fn decode(filename: &str) -> Result<(String, Vec<u8>), String> {
+    if !filename.starts_with("data:") {
+        return Err(format!("Not a data URL: {}", filename));
+    }
+    let content = &filename["data:".len()..];
+    let separator = content
+        .find(',')
+        .ok_or_else(|| "Missing ',' in data URL".to_string())?;
+
+    let (header, payload) = (&content[..separator], &content[separator + 1..]);
+
+    let (media_type, is_base64) = if header.ends_with(";base64") {
+        (&header[..header.len() - ";base64".len()], true)
+    } else {
+        (header, false)
+    };
+
+    let media_type = if media_type.is_empty() {
+        DEFAULT_MEDIA_TYPE.to_string()
+    } else if media_type.starts_with(';') {
+        format!("text/plain{}", media_type)
+    } else {
+        media_type.to_string()
+    };
+
+    let data: Vec<u8> = percent_decode(payload.as_bytes()).collect();
+    let data = if is_base64 {
+        let encoded: Vec<u8> = data
+            .into_iter()
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .collect();
+        base64::decode(&encoded).map_err(|msg| format!("Invalid base64 data URL: {}", msg))?
+    } else {
+        data
+    };
+
+    Ok((media_type, data))
+}
+
+#[derive(Debug)]
+pub struct DataReader {
+    pub filename: String,
+    pub media_type: String,
+    pub reader: MemoryReader,
+}
+
+impl DataReader {
+    pub fn get_media_type(&self) -> &str {
+        &self.media_type
+    }
+}
+
+impl Reader for DataReader {
+    fn new() -> DataReader {
+        DataReader {
+            filename: "".to_string(),
+            media_type: DEFAULT_MEDIA_TYPE.to_string(),
+            reader: MemoryReader::new(),
+        }
+    }
+
+    fn open(&mut self, filename: &str) -> Result<(), String> {
+        self.filename = filename.to_string();
+
+        let (media_type, data) = decode(filename)?;
+        let mut reader = MemoryReader::from_data(data);
+        reader.set_cache_size(self.reader.get_cache_size());
+        reader.set_max_end_position(self.reader.get_max_end_position());
+
+        self.media_type = media_type;
+        self.reader = reader;
+        Ok(())
+    }
+
+    fn get_position(&mut self) -> Result<u64, String> {
+        self.reader.get_position()
+    }
+
+    fn get_cache_size(&self) -> Option<usize> {
+        self.reader.get_cache_size()
+    }
+
+    fn set_cache_size(&mut self, cache_size: Option<usize>) {
+        self.reader.set_cache_size(cache_size)
+    }
+
+    fn get_max_end_position(&self) -> Option<u64> {
+        self.reader.get_max_end_position()
+    }
+
+    fn set_max_end_position(&mut self, max_end_position: Option<u64>) {
+        self.reader.set_max_end_position(max_end_position)
+    }
+
+    fn get_size(&mut self) -> Result<u64, String> {
+        self.reader.get_size()
+    }
+}
+
+impl Read for DataReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.reader.read(buf)
+    }
+}
+
+impl BufRead for DataReader {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount)
+    }
+}
+
+impl ReadAt for DataReader {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        self.reader.read_at(offset, buf)
+    }
+}
+
+impl Seek for DataReader {
+    fn seek(&mut self, seek_from: SeekFrom) -> Result<u64, Error> {
+        self.reader.seek(seek_from)
+    }
+}
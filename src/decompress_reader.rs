@@ -0,0 +1,443 @@
+#[cfg(feature = "bzip2")]
+use bzip2::read::BzDecoder;
+#[cfg(feature = "gzip")]
+use flate2::read::MultiGzDecoder;
+#[cfg(feature = "xz")]
+use xz2::read::XzDecoder;
+#[cfg(feature = "zstd")]
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+use std::cmp;
+use std::fmt;
+#[cfg(feature = "zstd")]
+use std::io::BufReader;
+use std::io::{BufRead, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::sync::Mutex;
+
+use reader::{ReadAt, Reader};
+use stats::TransferStats;
+use MainReader;
+
+const DECODE_BUFFER_SIZE: usize = 64 * 1024;
+/// Decompressed data kept to seek backward without decoding again.
+const REWIND_SIZE: usize = 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Xz,
+    Bzip2,
+}
+
+impl Compression {
+    pub fn from_extension(filename: &str) -> Option<Compression> {
+        let filename = filename.to_lowercase();
+        if filename.ends_with(".gz") || filename.ends_with(".gzip") {
+            return Some(Compression::Gzip);
+        }
+        if filename.ends_with(".zst") || filename.ends_with(".zstd") {
+            return Some(Compression::Zstd);
+        }
+        if filename.ends_with(".xz") {
+            return Some(Compression::Xz);
+        }
+        if filename.ends_with(".bz2") {
+            return Some(Compression::Bzip2);
+        }
+        None
+    }
+
+    pub fn from_magic(header: &[u8]) -> Option<Compression> {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            return Some(Compression::Gzip);
+        }
+        if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            return Some(Compression::Zstd);
+        }
+        if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            return Some(Compression::Xz);
+        }
+        if header.starts_with(b"BZh") {
+            return Some(Compression::Bzip2);
+        }
+        None
+    }
+}
+
+/// Decompressing reader over the compressed stream `R`.
+trait Decode<R>: Read {
+    fn get_ref(&self) -> &R;
+    fn get_mut(&mut self) -> &mut R;
+    fn into_inner(self: Box<Self>) -> R;
+}
+
+#[cfg(feature = "gzip")]
+impl<R: Read> Decode<R> for MultiGzDecoder<R> {
+    fn get_ref(&self) -> &R {
+        MultiGzDecoder::get_ref(self)
+    }
+
+    fn get_mut(&mut self) -> &mut R {
+        MultiGzDecoder::get_mut(self)
+    }
+
+    fn into_inner(self: Box<Self>) -> R {
+        MultiGzDecoder::into_inner(*self)
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl<R: Read> Decode<R> for ZstdDecoder<'static, BufReader<R>> {
+    fn get_ref(&self) -> &R {
+        ZstdDecoder::get_ref(self).get_ref()
+    }
+
+    fn get_mut(&mut self) -> &mut R {
+        ZstdDecoder::get_mut(self).get_mut()
+    }
+
+    fn into_inner(self: Box<Self>) -> R {
+        self.finish().into_inner()
+    }
+}
+
+#[cfg(feature = "xz")]
+impl<R: Read> Decode<R> for XzDecoder<R> {
+    fn get_ref(&self) -> &R {
+        XzDecoder::get_ref(self)
+    }
+
+    fn get_mut(&mut self) -> &mut R {
+        XzDecoder::get_mut(self)
+    }
+
+    fn into_inner(self: Box<Self>) -> R {
+        XzDecoder::into_inner(*self)
+    }
+}
+
+#[cfg(feature = "bzip2")]
+impl<R: Read> Decode<R> for BzDecoder<R> {
+    fn get_ref(&self) -> &R {
+        BzDecoder::get_ref(self)
+    }
+
+    fn get_mut(&mut self) -> &mut R {
+        BzDecoder::get_mut(self)
+    }
+
+    fn into_inner(self: Box<Self>) -> R {
+        BzDecoder::into_inner(*self)
+    }
+}
+
+type BoxedDecoder<R> = Box<dyn Decode<R> + Send>;
+
+#[cfg(feature = "gzip")]
+fn gzip_decoder<R: Read + Send + 'static>(reader: R) -> Result<BoxedDecoder<R>, Error> {
+    Ok(Box::new(MultiGzDecoder::new(reader)))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_decoder<R: Read + Send + 'static>(_reader: R) -> Result<BoxedDecoder<R>, Error> {
+    Err(Error::new(
+        ErrorKind::Other,
+        "gzip decompression is not enabled",
+    ))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decoder<R: Read + Send + 'static>(reader: R) -> Result<BoxedDecoder<R>, Error> {
+    Ok(Box::new(ZstdDecoder::new(reader)?))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decoder<R: Read + Send + 'static>(_reader: R) -> Result<BoxedDecoder<R>, Error> {
+    Err(Error::new(
+        ErrorKind::Other,
+        "zstd decompression is not enabled",
+    ))
+}
+
+#[cfg(feature = "xz")]
+fn xz_decoder<R: Read + Send + 'static>(reader: R) -> Result<BoxedDecoder<R>, Error> {
+    Ok(Box::new(XzDecoder::new_multi_decoder(reader)))
+}
+
+#[cfg(not(feature = "xz"))]
+fn xz_decoder<R: Read + Send + 'static>(_reader: R) -> Result<BoxedDecoder<R>, Error> {
+    Err(Error::new(
+        ErrorKind::Other,
+        "xz decompression is not enabled",
+    ))
+}
+
+#[cfg(feature = "bzip2")]
+fn bzip2_decoder<R: Read + Send + 'static>(reader: R) -> Result<BoxedDecoder<R>, Error> {
+    Ok(Box::new(BzDecoder::new(reader)))
+}
+
+#[cfg(not(feature = "bzip2"))]
+fn bzip2_decoder<R: Read + Send + 'static>(_reader: R) -> Result<BoxedDecoder<R>, Error> {
+    Err(Error::new(
+        ErrorKind::Other,
+        "bzip2 decompression is not enabled",
+    ))
+}
+
+/// Decompress the stream of another reader on the fly.
+///
+/// Decoding only goes forward: seeking forward decodes and discards data,
+/// seeking backward is limited to the last mebibyte of decompressed data.
+/// Seeking from the end is not supported as the decompressed size is unknown.
+pub struct DecompressReader<R: Read> {
+    pub compression: Compression,
+    pub position: u64,
+    /// Behind a mutex as some decoders are not `Sync`, unlike the reader.
+    decoder: Mutex<BoxedDecoder<R>>,
+    /// Data decoded last, delivered or not.
+    history: Vec<u8>,
+    /// Size of the end of `history` not delivered yet.
+    pending: usize,
+    max_end_position: Option<u64>,
+}
+
+impl<R: Read> fmt::Debug for DecompressReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DecompressReader")
+            .field("compression", &self.compression)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<R: Reader + Read + Seek + Send + 'static> DecompressReader<R> {
+    pub fn create(reader: R, compression: Compression) -> Result<DecompressReader<R>, Error> {
+        let decoder = match compression {
+            Compression::Gzip => gzip_decoder(reader)?,
+            Compression::Zstd => zstd_decoder(reader)?,
+            Compression::Xz => xz_decoder(reader)?,
+            Compression::Bzip2 => bzip2_decoder(reader)?,
+        };
+        Ok(DecompressReader {
+            compression,
+            position: 0,
+            decoder: Mutex::new(decoder),
+            history: vec![],
+            pending: 0,
+            max_end_position: None,
+        })
+    }
+
+    /// Select the compression from the `filename` extension, or else from the
+    /// magic bytes at the beginning of the stream.
+    pub fn detect(mut reader: R, filename: &str) -> Result<DecompressReader<R>, Error> {
+        let compression = detect_compression(&mut reader, filename)?.ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("unable to detect compression of {}", filename),
+            )
+        })?;
+
+        DecompressReader::create(reader, compression)
+    }
+
+    pub fn get_position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        self.decoder.get_mut().unwrap().get_mut()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.decoder.into_inner().unwrap().into_inner()
+    }
+
+    /// Cache size of the compressed stream reader.
+    pub fn get_cache_size(&self) -> Option<usize> {
+        self.decoder.lock().unwrap().get_ref().get_cache_size()
+    }
+
+    pub fn set_cache_size(&mut self, cache_size: Option<usize>) {
+        self.get_mut().set_cache_size(cache_size)
+    }
+
+    pub fn get_max_end_position(&self) -> Option<u64> {
+        self.max_end_position
+    }
+
+    /// Last decompressed position to read, included.
+    pub fn set_max_end_position(&mut self, max_end_position: Option<u64>) {
+        self.max_end_position = max_end_position;
+    }
+
+    /// Always an error, the decompressed size is only known at the end.
+    pub fn get_size(&self) -> Result<u64, String> {
+        Err("the size of a decompressed stream is unknown".to_string())
+    }
+}
+
+/// Compression of `filename` from its extension, or else from the magic bytes
+/// at the beginning of `reader`, which is rewound. `None` if not compressed.
+pub fn detect_compression<R: Read + Seek>(
+    reader: &mut R,
+    filename: &str,
+) -> Result<Option<Compression>, Error> {
+    if let Some(compression) = Compression::from_extension(filename) {
+        return Ok(Some(compression));
+    }
+
+    let mut header = [0; 6];
+    let mut header_size = 0;
+    while header_size < header.len() {
+        let readed_size = reader.read(&mut header[header_size..])?;
+        if readed_size == 0 {
+            break;
+        }
+        header_size += readed_size;
+    }
+    reader.seek(SeekFrom::Start(0))?;
+
+    Ok(Compression::from_magic(&header[..header_size]))
+}
+
+/// Open `filename` with `reader` and its settings, then decompress it.
+/// `None` when the content is not compressed.
+pub fn open_with(
+    filename: &str,
+    mut reader: MainReader,
+) -> Result<Option<DecompressReader<MainReader>>, String> {
+    reader.open(filename)?;
+    match detect_compression(&mut reader, filename).map_err(|error| error.to_string())? {
+        Some(compression) => DecompressReader::create(reader, compression)
+            .map(Some)
+            .map_err(|error| error.to_string()),
+        None => Ok(None),
+    }
+}
+
+impl DecompressReader<MainReader> {
+    /// Transfer counters of the compressed stream reader.
+    pub fn get_stats(&self) -> Option<TransferStats> {
+        self.decoder.lock().unwrap().get_ref().get_stats()
+    }
+}
+
+impl<R: Read> DecompressReader<R> {
+    fn decode(&mut self) -> Result<(), Error> {
+        if self.history.len() >= 2 * REWIND_SIZE {
+            let end = self.history.len() - REWIND_SIZE;
+            self.history.drain(..end);
+        }
+
+        let start = self.history.len();
+        self.history.resize(start + DECODE_BUFFER_SIZE, 0);
+        let result = self
+            .decoder
+            .get_mut()
+            .unwrap()
+            .read(&mut self.history[start..]);
+        let readed_size = *result.as_ref().unwrap_or(&0);
+        self.history.truncate(start + readed_size);
+        self.pending = readed_size;
+        result.map(|_| ())
+    }
+}
+
+impl<R: Read> Read for DecompressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let readed_size = {
+            let data = self.fill_buf()?;
+            let readed_size = cmp::min(data.len(), buf.len());
+            buf[..readed_size].clone_from_slice(&data[..readed_size]);
+            readed_size
+        };
+        self.consume(readed_size);
+        Ok(readed_size)
+    }
+}
+
+impl<R: Read> BufRead for DecompressReader<R> {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        if self.pending == 0 {
+            self.decode()?;
+        }
+        let available = match self.max_end_position {
+            Some(max_end_position) => cmp::min(
+                self.pending as u64,
+                max_end_position
+                    .saturating_add(1)
+                    .saturating_sub(self.position),
+            ) as usize,
+            None => self.pending,
+        };
+        let start = self.history.len() - self.pending;
+        Ok(&self.history[start..start + available])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        let amount = cmp::min(amount, self.pending);
+        self.pending -= amount;
+        self.position += amount as u64;
+    }
+}
+
+impl<R: Read> Seek for DecompressReader<R> {
+    fn seek(&mut self, seek_from: SeekFrom) -> Result<u64, Error> {
+        let position = match seek_from {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+            SeekFrom::End(_) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "seek from end is not supported on a decompressed stream",
+                ));
+            }
+        };
+        if position < 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        }
+
+        let position = position as u64;
+        if position < self.position {
+            let delivered = (self.history.len() - self.pending) as u64;
+            let rewind = self.position - position;
+            if rewind > delivered {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "unable to seek {} bytes backward on a decompressed stream, \
+                         only the last {} bytes are kept",
+                        rewind, delivered
+                    ),
+                ));
+            }
+            self.pending += rewind as usize;
+            self.position = position;
+        }
+
+        while self.position < position {
+            let available = self.fill_buf()?.len() as u64;
+            if available == 0 {
+                break;
+            }
+            self.consume(cmp::min(available, position - self.position) as usize);
+        }
+        Ok(self.position)
+    }
+}
+
+impl<R: Read> ReadAt for DecompressReader<R> {
+    /// Always an error, as data is only decoded in order.
+    fn read_at(&self, _offset: u64, _buf: &mut [u8]) -> Result<usize, Error> {
+        Err(Error::new(
+            ErrorKind::Other,
+            "positional reads are not supported on a decompressed stream",
+        ))
+    }
+}
@@ -0,0 +1,119 @@
+use checksum::Checksum;
+
+use std::error;
+use std::fmt;
+use std::io;
+
+/// Error returned by operations that need to tell failure causes apart.
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or no response was received (DNS, connection, ...).
+    Request(String),
+    /// The server answered with an unexpected status code.
+    Status(u16),
+    Timeout(TimeoutKind),
+    Cancelled,
+    /// The content digest differs from the expected one.
+    ChecksumMismatch {
+        expected: Checksum,
+        actual: Checksum,
+    },
+    /// The response body does not match its headers (truncated body, digest mismatch, ...).
+    Corrupted(String),
+    Io(io::Error),
+    /// The operation is not supported with this configuration.
+    Unsupported(String),
+    Other(String),
+}
+
+/// Which time limit was exceeded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeoutKind {
+    /// The connection could not be established or the response did not arrive in time.
+    Request,
+    /// The response body did not arrive in time.
+    Read,
+    /// The overall operation deadline has passed.
+    Deadline,
+    /// The transfer rate fell under the configured minimum.
+    Stall,
+}
+
+impl Error {
+    /// Whether the same operation may succeed if tried again later.
+    pub fn is_retryable(&self) -> bool {
+        match *self {
+            Error::Request(_) => true,
+            Error::Status(status) => status == 408 || status == 429 || status >= 500,
+            Error::Timeout(kind) => kind != TimeoutKind::Deadline,
+            Error::Cancelled => false,
+            Error::ChecksumMismatch { .. } => false,
+            Error::Corrupted(_) => true,
+            Error::Io(ref error) => match error.kind() {
+                io::ErrorKind::Interrupted
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted => true,
+                _ => false,
+            },
+            Error::Unsupported(_) => false,
+            Error::Other(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Request(ref msg) => write!(f, "request failed: {}", msg),
+            Error::Status(status) => write!(f, "bad response status: {}", status),
+            Error::Timeout(kind) => write!(f, "{:?} timeout", kind),
+            Error::Cancelled => write!(f, "operation cancelled"),
+            Error::ChecksumMismatch {
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "checksum mismatch: expected {}, got {}",
+                expected, actual
+            ),
+            Error::Corrupted(ref msg) => write!(f, "corrupted response: {}", msg),
+            Error::Io(ref error) => write!(f, "{}", error),
+            Error::Unsupported(ref msg) => write!(f, "unsupported: {}", msg),
+            Error::Other(ref msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        if error.get_ref().map_or(false, |inner| inner.is::<Error>()) {
+            return *error.into_inner().unwrap().downcast::<Error>().unwrap();
+        }
+        Error::Io(error)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> io::Error {
+        match error {
+            Error::Io(error) => error,
+            Error::Timeout(_) => io::Error::new(io::ErrorKind::TimedOut, error),
+            error => io::Error::new(io::ErrorKind::Other, error),
+        }
+    }
+}
+
+impl From<String> for Error {
+    fn from(msg: String) -> Error {
+        Error::Other(msg)
+    }
+}
+
+impl From<Error> for String {
+    fn from(error: Error) -> String {
+        error.to_string()
+    }
+}
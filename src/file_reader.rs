@@ -1,11 +1,26 @@
+#[cfg(feature = "mmap")]
+use memmap::Mmap;
+
+use std::cmp;
 use std::fs;
 use std::fs::File;
+#[cfg(unix)]
+use std::os::unix::fs::FileExt;
+#[cfg(windows)]
+use std::os::windows::fs::FileExt;
 use std::path::Path;
+use std::time::Instant;
+
+const DEFAULT_CACHE_SIZE: usize = 64 * 1024;
 
-use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+use std::io::{BufRead, Error, ErrorKind, Read, Seek, SeekFrom};
 
 use buffer::Buffer;
-use reader::Reader;
+use error;
+use listing::Entry;
+use metadata::Metadata;
+use reader::{ReadAt, Reader};
+use stats::TransferStats;
 
 #[derive(Debug)]
 pub struct FileReader {
@@ -13,24 +28,133 @@ pub struct FileReader {
     pub position: u64,
     pub file: Option<File>,
     pub buffer: Buffer,
+    pub stats: TransferStats,
+    /// Map the file in memory on `open`, falling back to regular reads when it cannot be mapped.
+    pub use_mmap: bool,
+    #[cfg(feature = "mmap")]
+    pub mmap: Option<Mmap>,
 }
 
 pub fn exists(filename: &str) -> bool {
     Path::new(filename).exists()
 }
 
+pub fn stat(filename: &str) -> Result<Option<Metadata>, error::Error> {
+    match fs::metadata(filename) {
+        Ok(metadata) => Ok(Some(Metadata {
+            size: if metadata.is_dir() {
+                None
+            } else {
+                Some(metadata.len())
+            },
+            modified: metadata.modified().ok(),
+            content_type: None,
+            is_dir: metadata.is_dir(),
+        })),
+        Err(ref error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+fn copy_at(data: &[u8], position: u64, buf: &mut [u8]) -> usize {
+    if position >= data.len() as u64 {
+        return 0;
+    }
+    let start = position as usize;
+    let size = cmp::min(buf.len(), data.len() - start);
+    buf[..size].copy_from_slice(&data[start..start + size]);
+    size
+}
+
+impl FileReader {
+    /// Content of the mapped file, when opened in mmap mode.
+    #[cfg(feature = "mmap")]
+    pub fn mapped(&self) -> Option<&[u8]> {
+        self.mmap.as_ref().map(|mmap| &mmap[..])
+    }
+
+    #[cfg(not(feature = "mmap"))]
+    pub fn mapped(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Borrow `length` bytes at `start` from the mapping without copying them,
+    /// truncated at the end of the file. Return `None` when the file is not mapped.
+    pub fn get_slice(&self, start: u64, length: usize) -> Option<&[u8]> {
+        let data = self.mapped()?;
+        let start = cmp::min(start, data.len() as u64) as usize;
+        let end = cmp::min(start.saturating_add(length), data.len());
+        Some(&data[start..end])
+    }
+
+    /// Size of the file on disk, mapping it again when it grew since `open`.
+    pub fn refresh_size(&mut self) -> Result<u64, error::Error> {
+        let size = fs::metadata(&self.filename)?.len();
+        let grown = self
+            .mapped()
+            .map_or(false, |data| (data.len() as u64) < size);
+        if grown {
+            self.map();
+        }
+        Ok(size)
+    }
+
+    #[cfg(feature = "mmap")]
+    fn map(&mut self) {
+        self.mmap = None;
+        if let Some(ref file) = self.file {
+            match unsafe { Mmap::map(file) } {
+                Ok(mmap) => self.mmap = Some(mmap),
+                Err(msg) => warn!(
+                    "unable to map {}, using regular reads: {}",
+                    self.filename, msg
+                ),
+            }
+        }
+    }
+
+    #[cfg(not(feature = "mmap"))]
+    fn map(&mut self) {
+        warn!(
+            "mmap feature is disabled, using regular reads for {}",
+            self.filename
+        );
+    }
+}
+
+pub fn list(path: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = vec![];
+    for dir_entry in fs::read_dir(path).map_err(|e| e.to_string())? {
+        let dir_entry = dir_entry.map_err(|e| e.to_string())?;
+        let metadata = dir_entry.metadata().map_err(|e| e.to_string())?;
+
+        entries.push(Entry {
+            name: dir_entry.file_name().to_string_lossy().into_owned(),
+            size: if metadata.is_dir() {
+                None
+            } else {
+                Some(metadata.len())
+            },
+            modified: metadata.modified().ok(),
+            is_dir: metadata.is_dir(),
+        });
+    }
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
 impl Reader for FileReader {
     fn new() -> FileReader {
         FileReader {
             filename: "".to_string(),
             position: 0,
             file: None,
-            buffer: Buffer {
-                size: None,
-                position: 0,
-                max_end_position: None,
-                buffer: vec![],
-            },
+            buffer: Buffer::create(),
+            stats: TransferStats::default(),
+            use_mmap: false,
+            #[cfg(feature = "mmap")]
+            mmap: None,
         }
     }
 
@@ -41,6 +165,10 @@ impl Reader for FileReader {
             Err(msg) => Err(msg.to_string()),
             Ok(file) => {
                 self.file = Some(file);
+                self.position = 0;
+                if self.use_mmap {
+                    self.map();
+                }
                 Ok(())
             }
         }
@@ -67,6 +195,9 @@ impl Reader for FileReader {
     }
 
     fn get_size(&mut self) -> Result<u64, String> {
+        if let Some(data) = self.mapped() {
+            return Ok(data.len() as u64);
+        }
         let metadata = fs::metadata(self.filename.clone()).map_err(|e| e.to_string())?;
         Ok(metadata.len())
     }
@@ -74,18 +205,127 @@ impl Reader for FileReader {
 
 impl Read for FileReader {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
-        if let Some(ref mut file_reader) = self.file {
-            let readed_size = file_reader.read(buf)?;
-            self.position += readed_size as u64;
-            Ok(readed_size)
-        } else {
-            Err(Error::new(ErrorKind::Other, "No file opened"))
+        let cached_size = self.buffer.get_cached_size();
+        if cached_size > 0 {
+            let size = cmp::min(buf.len(), cached_size);
+            self.buffer.get_data(&mut buf[..size]);
+            self.position += size as u64;
+            self.stats.bytes_delivered += size as u64;
+            return Ok(size);
+        }
+
+        let start = Instant::now();
+        let readed_size = match self.mapped() {
+            Some(data) => copy_at(data, self.position, buf),
+            None => match self.file {
+                Some(ref mut file_reader) => file_reader.read(buf)?,
+                None => return Err(Error::new(ErrorKind::Other, "No file opened")),
+            },
+        };
+        self.position += readed_size as u64;
+
+        self.stats.requests += 1;
+        self.stats.bytes_fetched += readed_size as u64;
+        self.stats.bytes_delivered += readed_size as u64;
+        self.stats.latency += start.elapsed();
+        Ok(readed_size)
+    }
+}
+
+/// A mapped file is served directly from the mapping, otherwise data is
+/// read ahead into the cache.
+impl BufRead for FileReader {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        if self.mapped().is_some() {
+            let data = self.mapped().unwrap();
+            let start = cmp::min(self.position, data.len() as u64) as usize;
+            return Ok(&data[start..]);
+        }
+
+        if self.buffer.get_cached_size() == 0 {
+            let start = Instant::now();
+            let mut data = vec![0; self.buffer.size.unwrap_or(DEFAULT_CACHE_SIZE)];
+            let readed_size = match self.file {
+                Some(ref mut file_reader) => file_reader.read(&mut data)?,
+                None => return Err(Error::new(ErrorKind::Other, "No file opened")),
+            };
+            self.buffer.append_data(&data[..readed_size]);
+
+            self.stats.requests += 1;
+            self.stats.bytes_fetched += readed_size as u64;
+            self.stats.latency += start.elapsed();
+        }
+        Ok(self.buffer.get_cached_data())
+    }
+
+    fn consume(&mut self, amount: usize) {
+        let amount = match self.mapped() {
+            Some(data) => cmp::min(
+                amount as u64,
+                (data.len() as u64).saturating_sub(self.position),
+            ),
+            None => {
+                let amount = cmp::min(amount, self.buffer.get_cached_size());
+                self.buffer.consume(amount);
+                amount as u64
+            }
+        };
+        self.position += amount;
+        self.stats.bytes_delivered += amount;
+    }
+}
+
+impl ReadAt for FileReader {
+    /// Use `pread` on Unix. On Windows the file cursor is moved, so the
+    /// shared handle should only be used through `read_at` there.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        if let Some(data) = self.mapped() {
+            return Ok(copy_at(data, offset, buf));
+        }
+        match self.file {
+            #[cfg(unix)]
+            Some(ref file) => file.read_at(buf, offset),
+            #[cfg(windows)]
+            Some(ref file) => file.seek_read(buf, offset),
+            None => Err(Error::new(ErrorKind::Other, "No file opened")),
         }
     }
 }
 
 impl Seek for FileReader {
     fn seek(&mut self, seek_from: SeekFrom) -> Result<u64, Error> {
+        if let Some(size) = self.mapped().map(|data| data.len() as i64) {
+            let position = match seek_from {
+                SeekFrom::Start(offset) => offset as i64,
+                SeekFrom::Current(offset) => self.position as i64 + offset,
+                SeekFrom::End(offset) => size + offset,
+            };
+            if position < 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "invalid seek to a negative position",
+                ));
+            }
+            self.position = position as u64;
+            return Ok(self.position);
+        }
+
+        // The file cursor is ahead of the position by the cached data.
+        let seek_from = match seek_from {
+            SeekFrom::Current(offset) if self.buffer.get_cached_size() > 0 => {
+                let position = self.position as i64 + offset;
+                if position < 0 {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "invalid seek to a negative position",
+                    ));
+                }
+                SeekFrom::Start(position as u64)
+            }
+            seek_from => seek_from,
+        };
+        self.buffer.reset();
+
         if let Some(ref mut file_reader) = self.file {
             let seek_position = file_reader.seek(seek_from)?;
             self.position = seek_position;
@@ -0,0 +1,63 @@
+use cancellation::CancellationToken;
+use error::Error;
+
+use std::cmp;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Settings of the follow mode, where reaching the end of a file still being
+/// written waits for more data instead of returning 0.
+///
+/// Clones share the completion flag, so the writer side can call `complete`
+/// once the file is final.
+#[derive(Clone, Debug)]
+pub struct Follow {
+    /// Delay between two checks of the file size.
+    pub poll_interval: Duration,
+    /// Give up and return the end of file when the file did not grow for this long.
+    pub idle_timeout: Duration,
+    complete: Arc<AtomicBool>,
+}
+
+impl Follow {
+    pub fn new(poll_interval: Duration, idle_timeout: Duration) -> Follow {
+        Follow {
+            poll_interval,
+            idle_timeout,
+            complete: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Signal that the file will not grow anymore.
+    pub fn complete(&self) {
+        self.complete.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete.load(Ordering::SeqCst)
+    }
+
+    /// Call `grown` every `poll_interval` until it returns true, the file is
+    /// complete or the idle timeout elapsed. Return whether the file grew.
+    /// Cancelling `cancellation` interrupts the wait.
+    pub fn wait<F>(&self, cancellation: &CancellationToken, mut grown: F) -> Result<bool, Error>
+    where
+        F: FnMut() -> Result<bool, Error>,
+    {
+        let start = Instant::now();
+        loop {
+            // Read the flag first, so data written before completion is not missed.
+            let complete = self.is_complete();
+            if grown()? {
+                return Ok(true);
+            }
+
+            let elapsed = start.elapsed();
+            if complete || elapsed >= self.idle_timeout {
+                return Ok(false);
+            }
+            cancellation.sleep(cmp::min(self.poll_interval, self.idle_timeout - elapsed))?;
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use base64;
+use hmac::{Hmac, Mac};
+use httpdate;
+#[cfg(feature = "service-account")]
+use openssl::hash::MessageDigest;
+#[cfg(feature = "service-account")]
+use openssl::pkey::PKey;
+#[cfg(feature = "service-account")]
+use openssl::sign::Signer as RsaSigner;
+use reqwest::header::{HeaderMap, AUTHORIZATION, DATE};
+use reqwest::Url;
+use serde_json::{self, Value};
+use sha1::Sha1;
+
+use error::Error;
+use http_reader;
+use listing::{parse_iso8601, relative_name, xml_element, xml_elements, xml_unescape, Entry};
+use signer::{encode_path, to_header_value, Signer, TokenExchange};
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const READ_ONLY_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_only";
+const JWT_BEARER_GRANT: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+/// Lifetime requested for the signed assertion, the maximum accepted by Google.
+const ASSERTION_LIFETIME: u64 = 3600;
+/// Refresh a cached access token when it expires within this delay.
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug)]
+pub enum GcsAccess {
+    Anonymous,
+    Hmac { access_id: String, secret: String },
+    Token(String),
+    ServiceAccount(Arc<ServiceAccount>),
+}
+
+/// Service-account key used to obtain OAuth2 access tokens through a signed JWT.
+pub struct ServiceAccount {
+    pub client_email: String,
+    /// PKCS#8 private key in PEM format.
+    pub private_key: String,
+    pub token_uri: String,
+    token: Mutex<Option<(String, SystemTime)>>,
+}
+
+impl fmt::Debug for ServiceAccount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ServiceAccount")
+            .field("client_email", &self.client_email)
+            .field("token_uri", &self.token_uri)
+            .finish()
+    }
+}
+
+impl ServiceAccount {
+    pub fn new(client_email: &str, private_key: &str, token_uri: &str) -> ServiceAccount {
+        ServiceAccount {
+            client_email: client_email.to_string(),
+            private_key: private_key.to_string(),
+            token_uri: token_uri.to_string(),
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Parse a service-account JSON key file, as downloaded from the Cloud console.
+    pub fn from_json(json: &str) -> Result<ServiceAccount, String> {
+        let key: Value = serde_json::from_str(json).map_err(|msg| msg.to_string())?;
+        if key["type"] != "service_account" {
+            return Err("Not a service account key".to_string());
+        }
+
+        let field = |name: &str| {
+            key[name]
+                .as_str()
+                .map(|value| value.to_string())
+                .ok_or_else(|| format!("Missing {} in service account key", name))
+        };
+        let token_uri = field("token_uri")
+            .unwrap_or_else(|_| "https://oauth2.googleapis.com/token".to_string());
+
+        Ok(ServiceAccount::new(
+            &field("client_email")?,
+            &field("private_key")?,
+            &token_uri,
+        ))
+    }
+
+    /// Return a valid access token, posting a new signed assertion with `exchange`
+    /// when the cached one is missing or about to expire. Concurrent callers wait
+    /// for the exchange in progress instead of sending their own.
+    pub fn access_token(&self, exchange: &TokenExchange) -> Result<String, Error> {
+        let mut token = self.token.lock().unwrap();
+        if let Some((ref value, expires)) = *token {
+            if expires > SystemTime::now() + TOKEN_REFRESH_MARGIN {
+                return Ok(value.clone());
+            }
+        }
+
+        let assertion = self.assertion()?;
+        let body = exchange(
+            &self.token_uri,
+            &[("grant_type", JWT_BEARER_GRANT), ("assertion", &assertion)],
+        )?;
+
+        let response: Value = serde_json::from_str(&body).map_err(|msg| msg.to_string())?;
+        let value = response["access_token"]
+            .as_str()
+            .ok_or_else(|| "Missing access_token in token response".to_string())?
+            .to_string();
+        let expires_in = response["expires_in"]
+            .as_u64()
+            .unwrap_or(ASSERTION_LIFETIME);
+
+        *token = Some((
+            value.clone(),
+            SystemTime::now() + Duration::from_secs(expires_in),
+        ));
+        Ok(value)
+    }
+
+    /// Build the RS256-signed JWT exchanged against an access token at `token_uri`.
+    pub fn assertion(&self) -> Result<String, String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|msg| msg.to_string())?
+            .as_secs();
+
+        let header = json!({"alg": "RS256", "typ": "JWT"});
+        let claims = json!({
+            "iss": self.client_email,
+            "scope": READ_ONLY_SCOPE,
+            "aud": self.token_uri,
+            "iat": now,
+            "exp": now + ASSERTION_LIFETIME,
+        });
+        let message = format!(
+            "{}.{}",
+            base64::encode_config(&header.to_string(), base64::URL_SAFE_NO_PAD),
+            base64::encode_config(&claims.to_string(), base64::URL_SAFE_NO_PAD)
+        );
+        let signature = sign_rs256(&self.private_key, &message)?;
+
+        Ok(format!(
+            "{}.{}",
+            message,
+            base64::encode_config(&signature, base64::URL_SAFE_NO_PAD)
+        ))
+    }
+}
+
+#[cfg(feature = "service-account")]
+fn sign_rs256(private_key: &str, message: &str) -> Result<Vec<u8>, String> {
+    let key = PKey::private_key_from_pem(private_key.as_bytes()).map_err(|msg| msg.to_string())?;
+    let mut signer =
+        RsaSigner::new(MessageDigest::sha256(), &key).map_err(|msg| msg.to_string())?;
+    signer
+        .update(message.as_bytes())
+        .map_err(|msg| msg.to_string())?;
+    signer.sign_to_vec().map_err(|msg| msg.to_string())
+}
+
+#[cfg(not(feature = "service-account"))]
+fn sign_rs256(_private_key: &str, _message: &str) -> Result<Vec<u8>, String> {
+    Err("service account signing is not enabled".to_string())
+}
+
+#[derive(Clone, Debug)]
+pub struct GcsCredentials {
+    pub endpoint: String,
+    pub access: GcsAccess,
+}
+
+lazy_static! {
+    /// Credentials loaded from the environment by the first `gs://` access,
+    /// sharing the access token of a service account between readers.
+    static ref ENV_CREDENTIALS: Mutex<Option<GcsCredentials>> = Mutex::new(None);
+}
+
+impl GcsCredentials {
+    /// Build credentials from `GCS_HMAC_ACCESS_ID` and `GCS_HMAC_SECRET`, from
+    /// an OAuth2 token in `GOOGLE_OAUTH_ACCESS_TOKEN`, or from the service-account
+    /// key file named by `GOOGLE_APPLICATION_CREDENTIALS`. `STORAGE_EMULATOR_HOST`
+    /// overrides the endpoint (e.g. `http://127.0.0.1:4443` for fake-gcs-server).
+    pub fn from_env() -> Result<GcsCredentials, String> {
+        let endpoint = env::var("STORAGE_EMULATOR_HOST")
+            .unwrap_or_else(|_| "https://storage.googleapis.com".to_string());
+
+        let access = match (env::var("GCS_HMAC_ACCESS_ID"), env::var("GCS_HMAC_SECRET")) {
+            (Ok(access_id), Ok(secret)) => GcsAccess::Hmac { access_id, secret },
+            _ => match env::var("GOOGLE_OAUTH_ACCESS_TOKEN") {
+                Ok(token) => GcsAccess::Token(token),
+                Err(_) => match service_account_from_env()? {
+                    Some(account) => GcsAccess::ServiceAccount(Arc::new(account)),
+                    None => GcsAccess::Anonymous,
+                },
+            },
+        };
+
+        Ok(GcsCredentials { endpoint, access })
+    }
+
+    /// Credentials from the environment, loaded once per process so that
+    /// readers share the access token of a service account. A failed load is
+    /// tried again on the next call.
+    pub fn from_env_cached() -> Result<GcsCredentials, String> {
+        let mut credentials = ENV_CREDENTIALS.lock().unwrap();
+        if credentials.is_none() {
+            *credentials = Some(GcsCredentials::from_env()?);
+        }
+        Ok(credentials.clone().unwrap())
+    }
+
+    /// Translate `gs://bucket/object` into the object HTTP URL.
+    pub fn object_url(&self, filename: &str) -> Result<String, String> {
+        let path = filename.trim_start_matches("gs://").trim_start_matches('/');
+
+        if !path.contains('/') {
+            return Err(format!("Missing object name in {}", filename));
+        }
+
+        Ok(format!(
+            "{}/{}",
+            self.endpoint.trim_end_matches('/'),
+            encode_path(path)
+        ))
+    }
+
+    /// List objects and virtual directories under a `gs://bucket/prefix` URL.
+    pub fn list(&self, filename: &str) -> Result<Vec<Entry>, String> {
+        let path = filename.trim_start_matches("gs://").trim_start_matches('/');
+        let (bucket, prefix) = match path.find('/') {
+            Some(separator) => (&path[..separator], &path[separator + 1..]),
+            None => (path, ""),
+        };
+        let prefix = if prefix.is_empty() || prefix.ends_with('/') {
+            prefix.to_string()
+        } else {
+            format!("{}/", prefix)
+        };
+
+        let signer = Some(Signer::Gcs(self.clone()));
+        let mut entries = vec![];
+        let mut marker = "".to_string();
+
+        loop {
+            let mut url = Url::parse(&format!(
+                "{}/{}",
+                self.endpoint.trim_end_matches('/'),
+                bucket
+            ))
+            .map_err(|msg| msg.to_string())?;
+            url.query_pairs_mut()
+                .append_pair("delimiter", "/")
+                .append_pair("prefix", &prefix);
+            if !marker.is_empty() {
+                url.query_pairs_mut().append_pair("marker", &marker);
+            }
+
+            let body = http_reader::get_text(url.as_str(), &signer)?;
+            let (mut page, next_marker) = parse_bucket_list(&body, &prefix);
+            entries.append(&mut page);
+
+            match next_marker {
+                Some(next_marker) => marker = next_marker,
+                None => break,
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Authorize a request. A service account first obtains its access token
+    /// through `exchange`.
+    pub fn sign(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &mut HeaderMap,
+        exchange: &TokenExchange,
+    ) -> Result<(), Error> {
+        match self.access {
+            GcsAccess::Anonymous => Ok(()),
+            GcsAccess::Token(ref token) => {
+                let authorization = format!("Bearer {}", token);
+                headers.insert(AUTHORIZATION, to_header_value(&authorization)?);
+                Ok(())
+            }
+            GcsAccess::ServiceAccount(ref account) => {
+                let authorization = format!("Bearer {}", account.access_token(exchange)?);
+                headers.insert(AUTHORIZATION, to_header_value(&authorization)?);
+                Ok(())
+            }
+            GcsAccess::Hmac {
+                ref access_id,
+                ref secret,
+            } => {
+                if !headers.contains_key(DATE) {
+                    let date = httpdate::fmt_http_date(SystemTime::now());
+                    headers.insert(DATE, to_header_value(&date)?);
+                }
+
+                let string_to_sign = string_to_sign(method, url, headers)?;
+
+                let mut mac = Hmac::<Sha1>::new_varkey(secret.as_bytes())
+                    .map_err(|_| "Invalid GCS HMAC secret".to_string())?;
+                mac.input(string_to_sign.as_bytes());
+                let signature = base64::encode(&mac.result().code());
+
+                let authorization = format!("GOOG1 {}:{}", access_id, signature);
+                headers.insert(AUTHORIZATION, to_header_value(&authorization)?);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn service_account_from_env() -> Result<Option<ServiceAccount>, String> {
+    let path = match env::var("GOOGLE_APPLICATION_CREDENTIALS") {
+        Ok(path) => path,
+        Err(_) => return Ok(None),
+    };
+    let json =
+        fs::read_to_string(&path).map_err(|msg| format!("Unable to read {}: {}", path, msg))?;
+    ServiceAccount::from_json(&json)
+        .map(Some)
+        .map_err(|msg| format!("Unable to load {}: {}", path, msg))
+}
+
+fn string_to_sign(method: &str, url: &str, headers: &HeaderMap) -> Result<String, String> {
+    let url = Url::parse(url).map_err(|msg| msg.to_string())?;
+
+    let date = match headers.get(DATE) {
+        Some(value) => value.to_str().map_err(|msg| msg.to_string())?,
+        None => "",
+    };
+
+    let mut extension_headers: Vec<(String, String)> = vec![];
+    for (name, value) in headers.iter() {
+        if name.as_str().starts_with("x-goog-") {
+            let value = value.to_str().map_err(|msg| msg.to_string())?;
+            extension_headers.push((name.as_str().to_string(), value.trim().to_string()));
+        }
+    }
+    extension_headers.sort();
+
+    let mut string_to_sign = format!("{}\n\n\n{}\n", method, date);
+    for (name, value) in extension_headers {
+        string_to_sign += &format!("{}:{}\n", name, value);
+    }
+    string_to_sign += url.path();
+    Ok(string_to_sign)
+}
+
+/// Resolve a `gs://bucket/object` URL using credentials from the environment.
+pub fn resolve(filename: &str) -> Result<(String, Signer), String> {
+    let credentials = GcsCredentials::from_env_cached()?;
+    let url = credentials.object_url(filename)?;
+    Ok((url, Signer::Gcs(credentials)))
+}
+
+pub fn list(filename: &str) -> Result<Vec<Entry>, String> {
+    GcsCredentials::from_env_cached()?.list(filename)
+}
+
+/// Parse a ListBucketResult page, returning entries relative to `prefix` and the next marker.
+pub fn parse_bucket_list(body: &str, prefix: &str) -> (Vec<Entry>, Option<String>) {
+    let mut entries = vec![];
+
+    for content in xml_elements(body, "Contents") {
+        let name = match xml_element(content, "Key") {
+            Some(name) => xml_unescape(name),
+            None => continue,
+        };
+        if name == prefix {
+            continue;
+        }
+        entries.push(Entry {
+            name: relative_name(&name, prefix).to_string(),
+            size: xml_element(content, "Size").and_then(|size| size.parse().ok()),
+            modified: xml_element(content, "LastModified").and_then(parse_iso8601),
+            is_dir: false,
+        });
+    }
+
+    for common_prefix in xml_elements(body, "CommonPrefixes") {
+        if let Some(name) = xml_element(common_prefix, "Prefix") {
+            let name = xml_unescape(name);
+            entries.push(Entry {
+                name: relative_name(&name, prefix)
+                    .trim_end_matches('/')
+                    .to_string(),
+                size: None,
+                modified: None,
+                is_dir: true,
+            });
+        }
+    }
+
+    let is_truncated = xml_element(body, "IsTruncated") == Some("true");
+    let next_marker = xml_element(body, "NextMarker")
+        .map(|marker| marker.trim().to_string())
+        .filter(|marker| is_truncated && !marker.is_empty());
+
+    (entries, next_marker)
+}
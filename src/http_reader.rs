@@ -1,80 +1,545 @@
+use httpdate;
 use hyper::StatusCode;
 
 use hyperx::{
-    header::{ByteRangeSpec, ByteRangeSpec::FromTo, ContentRangeSpec, Range::Bytes},
+    header::{
+        ByteRangeSpec,
+        ByteRangeSpec::{AllFrom, FromTo},
+        ContentRangeSpec,
+        Range::Bytes,
+    },
     Headers,
 };
 
 use reqwest;
-use reqwest::{header, Client};
+use reqwest::{header, header::HeaderMap, Client, Method, RequestBuilder, Url};
 
 use buffer::Buffer;
-use reader::Reader;
+use cancellation::CancellationToken;
+use checksum::{self, Algorithm, Checksum};
+use error::{self, TimeoutKind};
+use listing::{parse_html_index, parse_propfind, Entry};
+use metadata::Metadata;
+use multipart::{byteranges_boundary, parse_byteranges, BytePart};
+use rate_limiter::RateLimiter;
+use reader::{ReadAt, Reader};
+use signer::Signer;
+use stats::{Metrics, RequestEvent, TransferStats};
+use timeouts::{remaining_time, Timeouts};
 
 use std::cmp;
-use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+use std::fmt;
+use std::io::{self, BufRead, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::mem;
 use std::str::FromStr;
-use std::time::Instant;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
-fn get_head(filename: &str) -> Result<reqwest::Response, reqwest::Error> {
+/// HTTP protocol spoken by a reader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// HTTP/1.1, connections being kept alive between the requests of a reader.
+    Http1,
+    /// HTTP/2 without upgrade nor ALPN negotiation, for `h2c` origins over
+    /// plain `http://`. Requests of a reader are multiplexed over a single
+    /// connection.
+    ///
+    /// HTTPS is not supported: the TLS backend does not negotiate `h2`
+    /// through ALPN, so opening an `https://` URL in this mode fails with
+    /// `Error::Unsupported` and such origins must be read with `Http1`.
+    Http2PriorKnowledge,
+}
+
+impl Default for HttpVersion {
+    fn default() -> HttpVersion {
+        HttpVersion::Http1
+    }
+}
+
+/// Client without a deadline, which is enforced while waiting on each
+/// transfer instead.
+fn build_client(timeouts: &Timeouts, http_version: HttpVersion) -> Result<Client, error::Error> {
+    let builder = timeouts.configure(Client::builder(), None)?;
+    let builder = match http_version {
+        HttpVersion::Http1 => builder,
+        HttpVersion::Http2PriorKnowledge => builder.h2_prior_knowledge(),
+    };
+    builder
+        .build()
+        .map_err(|error| error::Error::Request(error.to_string()))
+}
+
+fn is_https(filename: &str) -> bool {
+    Url::parse(filename)
+        .ok()
+        .map_or(false, |url| url.scheme() == "https")
+}
+
+fn get_head(
+    client: &Client,
+    filename: &str,
+    signer: &Option<Signer>,
+    watch: &Watch,
+) -> Result<Transfer, error::Error> {
     if filename.contains(".amazonaws.com") {
         let range = vec![FromTo(0, 0)];
 
         let mut headers = Headers::new();
         headers.set(Bytes(range));
-        let client = reqwest::Client::builder()
-            .default_headers(headers.into())
-            .build()
-            .unwrap();
-
-        client.get(filename).send()
+        Transfer::send(client.get(filename).headers(headers.into()), watch, 1)
     } else {
-        let client = Client::new();
-        client.head(filename).send()
+        let mut header_map = HeaderMap::new();
+        sign(signer, "HEAD", filename, &mut header_map, client, watch)?;
+
+        Transfer::send(client.head(filename).headers(header_map), watch, 1)
+    }
+}
+
+fn request_error(error: reqwest::Error, deadline: Option<Instant>) -> error::Error {
+    if error.is_timeout() {
+        return timeout_error(TimeoutKind::Request, deadline);
+    }
+    error::Error::Request(error.to_string())
+}
+
+fn timeout_error(kind: TimeoutKind, deadline: Option<Instant>) -> error::Error {
+    match deadline {
+        Some(deadline) if Instant::now() >= deadline => {
+            error::Error::Timeout(TimeoutKind::Deadline)
+        }
+        _ => error::Error::Timeout(kind),
+    }
+}
+
+/// Longest wait on the origin before the cancellation and the deadline are
+/// checked again.
+const POLL_INTERVAL_MILLIS: u64 = 50;
+
+/// Conditions interrupting a wait on the origin.
+struct Watch<'a> {
+    cancellation: &'a CancellationToken,
+    deadline: Option<Instant>,
+    /// Minimum transfer rate to keep while waiting: the bytes received since
+    /// a start instant.
+    progress: Option<(&'a Timeouts, Instant, u64)>,
+}
+
+impl<'a> Watch<'a> {
+    /// Also fail with a stall once `size` bytes since `start` fall under the
+    /// minimum rate of `timeouts`, even when no more data arrives.
+    fn with_progress(self, timeouts: &'a Timeouts, start: Instant, size: u64) -> Watch<'a> {
+        Watch {
+            progress: Some((timeouts, start, size)),
+            ..self
+        }
+    }
+
+    fn check(&self) -> Result<(), error::Error> {
+        self.cancellation.check()?;
+        if let Some(deadline) = self.deadline {
+            remaining_time(deadline)?;
+        }
+        if let Some((timeouts, start, size)) = self.progress {
+            timeouts.check_progress(start, size)?;
+        }
+        Ok(())
+    }
+
+    /// Wait for a message from a transfer thread, `None` once it stopped.
+    fn receive<T>(&self, receiver: &Receiver<T>) -> Result<Option<T>, error::Error> {
+        let interval = Duration::from_millis(POLL_INTERVAL_MILLIS);
+        loop {
+            self.check()?;
+            let timeout = match self.deadline {
+                Some(deadline) => cmp::min(interval, remaining_time(deadline)?),
+                None => interval,
+            };
+            match receiver.recv_timeout(timeout) {
+                Ok(message) => return Ok(Some(message)),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return Ok(None),
+            }
+        }
     }
 }
 
+/// Request sent by a helper thread, which then reads the body ahead chunk by
+/// chunk. Waiting for the connection, the headers or the body can thus be
+/// interrupted.
+///
+/// The blocking client cannot abort a pending wait, so a dropped transfer
+/// leaves its thread behind until the next chunk arrives or the client
+/// timeout expires (`Timeouts::read`, 30 seconds by default). The thread then
+/// stops and drops the response, which closes the connection.
+struct Transfer {
+    status: StatusCode,
+    headers: HeaderMap,
+    content_length: Option<u64>,
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    /// Received data not returned yet.
+    pending: Vec<u8>,
+}
+
+impl Transfer {
+    fn send(
+        request: RequestBuilder,
+        watch: &Watch,
+        chunk_size: usize,
+    ) -> Result<Transfer, error::Error> {
+        let (head_sender, head_receiver) = mpsc::channel();
+        let (chunk_sender, chunks) = mpsc::sync_channel(1);
+        thread::spawn(move || {
+            let mut response = match request.send() {
+                Ok(response) => response,
+                Err(error) => {
+                    let _ = head_sender.send(Err(error));
+                    return;
+                }
+            };
+            let head = (
+                response.status(),
+                response.headers().clone(),
+                response.content_length(),
+            );
+            if head_sender.send(Ok(head)).is_err() {
+                return;
+            }
+
+            let mut chunk = vec![0; chunk_size];
+            loop {
+                let result = match response.read(&mut chunk) {
+                    Ok(0) => return,
+                    Ok(readed_size) => Ok(chunk[..readed_size].to_vec()),
+                    Err(ref error) if error.kind() == ErrorKind::Interrupted => continue,
+                    Err(error) => Err(error),
+                };
+                let failed = result.is_err();
+                if chunk_sender.send(result).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        let head = watch
+            .receive(&head_receiver)?
+            .ok_or_else(|| error::Error::Request("request thread stopped".to_string()))?;
+        let (status, headers, content_length) =
+            head.map_err(|error| request_error(error, watch.deadline))?;
+        Ok(Transfer {
+            status,
+            headers,
+            content_length,
+            chunks,
+            pending: vec![],
+        })
+    }
+
+    fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Next data of the body, at most `max_size` bytes, `None` at its end.
+    fn read(&mut self, watch: &Watch, max_size: usize) -> Result<Option<Vec<u8>>, error::Error> {
+        if self.pending.is_empty() {
+            match watch.receive(&self.chunks)? {
+                Some(Ok(chunk)) => self.pending = chunk,
+                Some(Err(ref error)) if error.kind() == ErrorKind::TimedOut => {
+                    return Err(timeout_error(TimeoutKind::Read, watch.deadline));
+                }
+                Some(Err(error)) => return Err(error.into()),
+                None => return Ok(None),
+            }
+        }
+
+        if self.pending.len() <= max_size {
+            return Ok(Some(mem::replace(&mut self.pending, vec![])));
+        }
+        let rest = self.pending.split_off(max_size);
+        Ok(Some(mem::replace(&mut self.pending, rest)))
+    }
+}
+
+impl fmt::Debug for Transfer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Transfer")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+const BODY_CHUNK_SIZE: usize = 64 * 1024;
+const DEFAULT_CACHE_SIZE: usize = 64 * 1024;
+
 #[derive(Debug)]
 struct ResponseData {
     body_data: Vec<u8>,
-    file_size: u64,
+    file_size: Option<u64>,
+}
+
+/// Parsed `Content-Range` header. The range is missing with `bytes */length`
+/// and the length is unknown with `bytes start-end/*`.
+#[derive(Debug, PartialEq)]
+struct ContentRange {
+    range: Option<(u64, u64)>,
+    length: Option<u64>,
 }
 
-fn get_data(filename: &str, range: Vec<ByteRangeSpec>) -> Result<ResponseData, String> {
+fn send_range_request(
+    reader: &HttpReader,
+    range: Vec<ByteRangeSpec>,
+) -> Result<Transfer, error::Error> {
     let mut headers = Headers::new();
     headers.set(Bytes(range));
-    let client = reqwest::Client::builder()
-        .default_headers(headers.into())
-        .build()
-        .unwrap();
+    let mut header_map: HeaderMap = headers.into();
+    let client = reader.client()?;
+    sign(
+        &reader.signer,
+        "GET",
+        &reader.filename,
+        &mut header_map,
+        &client,
+        &reader.watch(),
+    )?;
 
-    let mut response = match client.get(filename).send() {
-        Ok(content) => content,
-        Err(_msg) => return Err("bad request".to_string()),
+    let chunk_size = match reader.rate_limiter {
+        Some(ref rate_limiter) => rate_limiter.chunk_size(BODY_CHUNK_SIZE),
+        None => BODY_CHUNK_SIZE,
     };
 
+    let response = Transfer::send(
+        client.get(&reader.filename).headers(header_map),
+        &reader.watch(),
+        chunk_size,
+    )?;
+
     let status = response.status();
 
-    if !(status == StatusCode::OK || status == StatusCode::PARTIAL_CONTENT) {
+    if !(status == StatusCode::OK
+        || status == StatusCode::PARTIAL_CONTENT
+        || status == StatusCode::RANGE_NOT_SATISFIABLE)
+    {
         error!("ERROR {:?}", response);
-        return Err("bad response status".to_string());
+        return Err(error::Error::Status(status.as_u16()));
     }
+    Ok(response)
+}
 
+/// Read the body of `response`, stopping once it holds `limit` bytes.
+fn read_body(
+    reader: &HttpReader,
+    response: &mut Transfer,
+    start: Instant,
+    limit: Option<u64>,
+) -> Result<Vec<u8>, error::Error> {
     let mut body: Vec<u8> = vec![];
-    let _result = response.copy_to(&mut body);
+    // Time spent waiting for the rate limiter does not count as a stall.
+    let mut progress_start = start;
+    loop {
+        let max_size = match limit {
+            Some(limit) if body.len() as u64 >= limit => break,
+            Some(limit) => cmp::min(limit - body.len() as u64, usize::max_value() as u64) as usize,
+            None => usize::max_value(),
+        };
+        let watch =
+            reader
+                .watch()
+                .with_progress(&reader.timeouts, progress_start, body.len() as u64);
+        let chunk = match response.read(&watch, max_size)? {
+            Some(chunk) => chunk,
+            None => break,
+        };
+        body.extend_from_slice(&chunk);
 
-    let file_size = match get_content_range(&response) {
-        Ok(length) => length.unwrap_or(0),
-        Err(_msg) => return Err("bad response header".to_string()),
+        if let Some(ref rate_limiter) = reader.rate_limiter {
+            progress_start += rate_limiter.acquire_with(
+                chunk.len() as u64,
+                &reader.cancellation,
+                reader.deadline,
+            )?;
+        }
+    }
+    Ok(body)
+}
+
+fn get_data(reader: &HttpReader, start: u64, end: u64) -> Result<ResponseData, error::Error> {
+    let request_start = Instant::now();
+    let mut response = send_range_request(reader, vec![FromTo(start, end)])?;
+    if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        return Ok(ResponseData {
+            body_data: vec![],
+            file_size: unsatisfied_range_length(&response),
+        });
+    }
+    // The server may ignore the range and send the whole content, of which
+    // only the beginning up to `end` is needed.
+    let limit = if response.status() == StatusCode::OK {
+        Some(end.saturating_add(1))
+    } else {
+        None
+    };
+    let body = read_body(reader, &mut response, request_start, limit)?;
+    let truncated = is_truncated(&response, &body, limit);
+    // Digests cover the whole content, which was not read to its end.
+    if !truncated {
+        verify_body(&response, &body)?;
+    }
+
+    let bad_header = || error::Error::Other("bad response header".to_string());
+    let (body_start, file_size) = match parse_content_range(&response).map_err(|_| bad_header())? {
+        Some(ContentRange {
+            range: Some((body_start, _)),
+            length,
+        }) => (body_start, length),
+        None if response.status() == StatusCode::OK => {
+            let file_size = if truncated {
+                response.content_length()
+            } else {
+                Some(body.len() as u64)
+            };
+            (0, file_size)
+        }
+        _ => return Err(bad_header()),
     };
 
     Ok(ResponseData {
-        body_data: body,
+        body_data: realign(body, body_start, start, end)?,
         file_size,
     })
 }
 
-fn get_content_range(response: &reqwest::Response) -> Result<Option<u64>, String> {
+/// Whether `body`, read up to `limit` bytes, misses the end of the content.
+fn is_truncated(response: &Transfer, body: &[u8], limit: Option<u64>) -> bool {
+    match limit {
+        Some(limit) => body.len() as u64 == limit && response.content_length() != Some(limit),
+        None => false,
+    }
+}
+
+/// Keep the bytes from `start` to `end` of a body beginning at `body_start`,
+/// for servers answering with a larger range or the whole content.
+fn realign(
+    mut body: Vec<u8>,
+    body_start: u64,
+    start: u64,
+    end: u64,
+) -> Result<Vec<u8>, error::Error> {
+    let body_end = body_start.saturating_add(body.len() as u64);
+    if body_start > start {
+        return Err(error::Error::Other(format!(
+            "requested range {}-{}, received {} bytes from {}",
+            start,
+            end,
+            body.len(),
+            body_start
+        )));
+    }
+    if body_end <= start {
+        return Ok(vec![]);
+    }
+
+    body.drain(..(start - body_start) as usize);
+    body.truncate((end - start + 1) as usize);
+    Ok(body)
+}
+
+/// Digests announced by `Content-MD5`, `Digest`, `Repr-Digest` and S3
+/// checksum headers. Unsupported algorithms and S3 composite checksums of
+/// multipart uploads are ignored.
+fn expected_checksums(headers: &HeaderMap) -> Vec<Checksum> {
+    let values = |name: &str| -> Vec<String> {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|value| value.trim().to_string())
+            .collect()
+    };
+
+    let mut checksums = vec![];
+    for value in values("content-md5") {
+        checksums.extend(Checksum::from_base64(Algorithm::Md5, &value));
+    }
+    for value in values("digest").into_iter().chain(values("repr-digest")) {
+        let mut name_value = value.splitn(2, '=');
+        let algorithm = match name_value.next().map(|name| name.trim().to_lowercase()) {
+            Some(ref name) if name == "md5" => Algorithm::Md5,
+            Some(ref name) if name == "sha" || name == "sha-1" => Algorithm::Sha1,
+            Some(ref name) if name == "sha-256" => Algorithm::Sha256,
+            _ => continue,
+        };
+        if let Some(digest) = name_value.next() {
+            checksums.extend(Checksum::from_base64(algorithm, digest.trim_matches(':')));
+        }
+    }
+    for value in values("x-amz-checksum-sha1") {
+        checksums.extend(Checksum::from_base64(Algorithm::Sha1, &value));
+    }
+    for value in values("x-amz-checksum-sha256") {
+        checksums.extend(Checksum::from_base64(Algorithm::Sha256, &value));
+    }
+    checksums
+}
+
+/// Check that the body holds the range announced by `Content-Range` and,
+/// when it is the whole object, that it matches the announced digests.
+fn verify_body(response: &Transfer, body: &[u8]) -> Result<(), error::Error> {
+    let content_range = response
+        .headers()
+        .get(header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| ContentRangeSpec::from_str(value).ok());
+
+    let full_object = match content_range {
+        Some(ContentRangeSpec::Bytes {
+            range: Some((start, end)),
+            instance_length,
+        }) => {
+            if end < start || body.len() as u64 != end - start + 1 {
+                return Err(error::Error::Corrupted(format!(
+                    "body holds {} bytes for range {}-{}",
+                    body.len(),
+                    start,
+                    end
+                )));
+            }
+            start == 0 && instance_length == Some(end + 1)
+        }
+        _ => response.status() == StatusCode::OK,
+    };
+    if !full_object {
+        return Ok(());
+    }
+
+    let expected = expected_checksums(response.headers());
+    let algorithms: Vec<Algorithm> = expected.iter().map(|checksum| checksum.algorithm).collect();
+    let actual = checksum::compute(body, &algorithms);
+    for checksum in &expected {
+        match checksum::verify(&actual, checksum) {
+            Err(error::Error::ChecksumMismatch { expected, actual }) => {
+                return Err(error::Error::Corrupted(format!(
+                    "expected digest {}, got {}",
+                    expected, actual
+                )));
+            }
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+fn parse_content_range(response: &Transfer) -> Result<Option<ContentRange>, String> {
     if let Some(content_range) = response.headers().get(header::CONTENT_RANGE) {
         let content_range_str = content_range
             .to_str()
@@ -84,15 +549,44 @@ fn get_content_range(response: &reqwest::Response) -> Result<Option<u64>, String
             .map_err(|msg| format!("Error parsing content range from str: {}", msg))?
         {
             ContentRangeSpec::Bytes {
-                instance_length: length,
+                range: Some((start, end)),
                 ..
-            } => Ok(length),
+            } if end < start => Err(format!("Invalid content range {}", content_range_str)),
+            ContentRangeSpec::Bytes {
+                range,
+                instance_length,
+            } => Ok(Some(ContentRange {
+                range,
+                length: instance_length,
+            })),
             ContentRangeSpec::Unregistered { .. } => {
                 Err("Unregistered, actually unsupported".to_string())
             }
         }
     } else {
-        Err("Missing content_range".to_string())
+        Ok(None)
+    }
+}
+
+fn head_size(response: &Transfer) -> Option<u64> {
+    match get_content_range(response) {
+        Ok(length) => length,
+        _ => response.content_length(),
+    }
+}
+
+/// Size announced with `bytes */size` by a 416 response.
+fn unsatisfied_range_length(response: &Transfer) -> Option<u64> {
+    match parse_content_range(response) {
+        Ok(Some(content_range)) => content_range.length,
+        _ => None,
+    }
+}
+
+fn get_content_range(response: &Transfer) -> Result<Option<u64>, String> {
+    match parse_content_range(response)? {
+        Some(content_range) => Ok(content_range.length),
+        None => Err("Missing content_range".to_string()),
     }
 }
 
@@ -102,16 +596,200 @@ pub struct HttpReader {
     pub file_size: Option<u64>,
     pub position: u64,
     pub buffer: Buffer,
+    pub signer: Option<Signer>,
+    pub timeouts: Timeouts,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    pub stats: TransferStats,
+    pub metrics: Option<Arc<dyn Metrics>>,
+    pub cancellation: CancellationToken,
+    /// Read with a single open-ended request instead of one request per
+    /// cache fill, for resources whose size is unknown.
+    pub streaming: bool,
+    /// Protocol of the requests, to set before `open`.
+    pub http_version: HttpVersion,
+    client: Option<Client>,
+    /// Behind a mutex as responses are not `Sync`, unlike the reader.
+    stream: Mutex<Option<Stream>>,
+    deadline: Option<Instant>,
+    /// Whether a first byte request succeeded without giving the size, not
+    /// to send it again on each `get_size`.
+    size_discovery_failed: bool,
+}
+
+/// Response of an open-ended request, with the position of its next byte.
+#[derive(Debug)]
+struct Stream {
+    response: Transfer,
+    position: u64,
 }
 
 pub fn exists(filename: &str) -> bool {
-    match get_head(filename) {
+    exists_with_signer(filename, &None)
+}
+
+pub fn exists_with_signer(filename: &str, signer: &Option<Signer>) -> bool {
+    let cancellation = CancellationToken::new();
+    let watch = Watch {
+        cancellation: &cancellation,
+        deadline: None,
+        progress: None,
+    };
+    let response = build_client(&Timeouts::default(), HttpVersion::default())
+        .and_then(|client| get_head(&client, filename, signer, &watch));
+    match response {
         Ok(resp) => resp.status().is_success(),
         Err(_msg) => false,
     }
 }
 
-fn get_data_range(position: u64, size: usize, max_end_position: Option<u64>) -> Vec<ByteRangeSpec> {
+/// Query the HEAD of `filename`, telling a missing resource apart from a failed check.
+pub fn stat(
+    filename: &str,
+    signer: &Option<Signer>,
+    timeouts: &Timeouts,
+) -> Result<Option<Metadata>, error::Error> {
+    let deadline = timeouts.deadline_from(Instant::now());
+    let client = build_client(timeouts, HttpVersion::default())?;
+    let cancellation = CancellationToken::new();
+    let watch = Watch {
+        cancellation: &cancellation,
+        deadline,
+        progress: None,
+    };
+    let response = get_head(&client, filename, signer, &watch)?;
+
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND || status == StatusCode::GONE {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(error::Error::Status(status.as_u16()));
+    }
+
+    let size = match get_content_range(&response) {
+        Ok(length) => length,
+        _ => response.content_length(),
+    };
+    let modified = response
+        .headers()
+        .get(header::LAST_MODIFIED)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| httpdate::parse_http_date(value).ok());
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+
+    Ok(Some(Metadata {
+        size,
+        modified,
+        content_type,
+        is_dir: false,
+    }))
+}
+
+const PROPFIND_BODY: &str = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+<D:propfind xmlns:D=\"DAV:\"><D:prop>\
+<D:resourcetype/><D:getcontentlength/><D:getlastmodified/>\
+</D:prop></D:propfind>";
+
+/// Sign a request, access tokens being obtained with `client` and interrupted
+/// like the request itself.
+fn sign(
+    signer: &Option<Signer>,
+    method: &str,
+    url: &str,
+    headers: &mut HeaderMap,
+    client: &Client,
+    watch: &Watch,
+) -> Result<(), error::Error> {
+    match *signer {
+        Some(ref signer) => signer.sign(
+            method,
+            url,
+            headers,
+            &|token_uri: &str, form: &[(&str, &str)]| post_form(client, token_uri, form, watch),
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Read the whole body of a successful response as text.
+fn read_text(mut transfer: Transfer, watch: &Watch) -> Result<String, error::Error> {
+    if !transfer.status().is_success() {
+        error!("ERROR {:?}", transfer);
+        return Err(error::Error::Status(transfer.status().as_u16()));
+    }
+
+    let mut body = vec![];
+    while let Some(chunk) = transfer.read(watch, BODY_CHUNK_SIZE)? {
+        body.extend_from_slice(&chunk);
+    }
+    String::from_utf8(body).map_err(|error| error::Error::Corrupted(error.to_string()))
+}
+
+/// POST an url-encoded form to `url` and return the response body as text.
+fn post_form(
+    client: &Client,
+    url: &str,
+    form: &[(&str, &str)],
+    watch: &Watch,
+) -> Result<String, error::Error> {
+    let transfer = Transfer::send(client.post(url).form(form), watch, BODY_CHUNK_SIZE)?;
+    read_text(transfer, watch)
+}
+
+/// Fetch the body of `filename` as text, signing the request when required.
+pub fn get_text(filename: &str, signer: &Option<Signer>) -> Result<String, String> {
+    let cancellation = CancellationToken::new();
+    let watch = Watch {
+        cancellation: &cancellation,
+        deadline: None,
+        progress: None,
+    };
+    let client = build_client(&Timeouts::default(), HttpVersion::default())?;
+
+    let mut header_map = HeaderMap::new();
+    sign(signer, "GET", filename, &mut header_map, &client, &watch)?;
+    let transfer = Transfer::send(
+        client.get(filename).headers(header_map),
+        &watch,
+        BODY_CHUNK_SIZE,
+    )?;
+    Ok(read_text(transfer, &watch)?)
+}
+
+/// List a WebDAV collection with PROPFIND, falling back to parsing an HTML index page.
+pub fn list(filename: &str) -> Result<Vec<Entry>, String> {
+    let cancellation = CancellationToken::new();
+    let watch = Watch {
+        cancellation: &cancellation,
+        deadline: None,
+        progress: None,
+    };
+    let client = build_client(&Timeouts::default(), HttpVersion::default())?;
+    let propfind = Method::from_bytes(b"PROPFIND").unwrap();
+
+    let request = client
+        .request(propfind, filename)
+        .header("Depth", "1")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(PROPFIND_BODY);
+
+    if let Ok(transfer) = Transfer::send(request, &watch, BODY_CHUNK_SIZE) {
+        if transfer.status() == StatusCode::MULTI_STATUS {
+            let body = read_text(transfer, &watch)?;
+            let url = Url::parse(filename).map_err(|msg| msg.to_string())?;
+            return Ok(parse_propfind(&body, url.path()));
+        }
+    }
+
+    let body = get_text(filename, &None)?;
+    Ok(parse_html_index(&body))
+}
+
+fn get_data_range(position: u64, size: usize, max_end_position: Option<u64>) -> (u64, u64) {
     let start = position;
     let end = match (position, size) {
         (0, 0) => 0,
@@ -124,16 +802,64 @@ fn get_data_range(position: u64, size: usize, max_end_position: Option<u64>) ->
         },
     };
 
-    vec![FromTo(start, end)]
+    (start, end)
 }
 
-fn load_data(reader: &mut HttpReader, size: usize) -> Result<Option<Vec<u8>>, String> {
+fn notify_request(
+    reader: &HttpReader,
+    position: u64,
+    size: u64,
+    latency: Duration,
+    error: Option<&error::Error>,
+) {
+    if let Some(ref metrics) = reader.metrics {
+        metrics.on_request(&RequestEvent {
+            url: &reader.filename,
+            method: "GET",
+            position,
+            size,
+            latency,
+            error,
+        });
+    }
+}
+
+fn record_request(
+    reader: &mut HttpReader,
+    method: &str,
+    position: u64,
+    size: u64,
+    latency: Duration,
+    error: Option<&error::Error>,
+) {
+    let event = RequestEvent {
+        url: &reader.filename,
+        method,
+        position,
+        size,
+        latency,
+        error,
+    };
+    reader.stats.record(&event);
+    if let Some(ref metrics) = reader.metrics {
+        metrics.on_request(&event);
+    }
+}
+
+/// Fetch `size` bytes. When `buffered`, the request starts where the cached
+/// data ends rather than at the reader position.
+fn load_data(
+    reader: &mut HttpReader,
+    size: usize,
+    buffered: bool,
+) -> Result<Option<Vec<u8>>, error::Error> {
     let start = Instant::now();
     info!("make HTTP request with request {:?} bytes", size);
 
-    let position = match reader.buffer.size {
-        Some(_) => reader.buffer.position,
-        None => reader.position,
+    let position = if buffered {
+        reader.buffer.position
+    } else {
+        reader.position
     };
 
     if let Some(total_file_size) = reader.file_size {
@@ -146,27 +872,350 @@ fn load_data(reader: &mut HttpReader, size: usize) -> Result<Option<Vec<u8>>, St
         }
     }
 
-    let range = get_data_range(position, size, reader.buffer.max_end_position);
-    let response = match get_data(&reader.filename, range) {
-        Ok(data) => data,
-        Err(msg) => return Err(msg),
-    };
+    let (range_start, range_end) = get_data_range(position, size, reader.buffer.max_end_position);
+    let result = get_data(reader, range_start, range_end);
 
     let elapsed = start.elapsed();
+    let fetched_size = match result {
+        Ok(ref response) => response.body_data.len() as u64,
+        Err(_) => 0,
+    };
+    record_request(
+        reader,
+        "GET",
+        position,
+        fetched_size,
+        elapsed,
+        result.as_ref().err(),
+    );
+    let response = result?;
+    if response.file_size.is_some() {
+        reader.file_size = response.file_size;
+    }
+    if response.body_data.is_empty() {
+        return Ok(None);
+    }
+
     if elapsed.as_secs() > 0 {
         warn!("Request duration {} seconds", elapsed.as_secs());
     }
 
     let new_position = position + response.body_data.len() as u64;
-    match reader.buffer.size {
-        Some(_) => {
-            reader.buffer.position = new_position;
+    if buffered {
+        reader.buffer.position = new_position;
+    } else {
+        reader.position = new_position;
+    }
+    Ok(Some(response.body_data))
+}
+
+impl HttpReader {
+    /// Read several `(start, length)` ranges with a single request when the
+    /// server answers with `multipart/byteranges`. Ranges missing from the
+    /// response, or refused by the server, are then requested one by one.
+    /// Like `read_at`, requests bypass the cache and are not counted in `stats`.
+    pub fn read_ranges(&self, ranges: &[(u64, u64)]) -> Result<Vec<Vec<u8>>, error::Error> {
+        self.cancellation.check()?;
+
+        let ranges: Vec<(u64, u64)> = ranges
+            .iter()
+            .map(|&(start, length)| match self.file_size {
+                Some(file_size) if start >= file_size => (start, 0),
+                Some(file_size) => (start, cmp::min(length, file_size - start)),
+                None => (start, length),
+            })
+            .collect();
+
+        let specs: Vec<ByteRangeSpec> = ranges
+            .iter()
+            .filter(|&&(_, length)| length > 0)
+            .map(|&(start, length)| FromTo(start, start + length - 1))
+            .collect();
+
+        let parts = if specs.len() > 1 {
+            let start = Instant::now();
+            let result = self.get_parts(specs, start);
+            let size = match result {
+                Ok(ref parts) => parts.iter().map(|part| part.data.len() as u64).sum(),
+                Err(_) => 0,
+            };
+            notify_request(
+                self,
+                ranges[0].0,
+                size,
+                start.elapsed(),
+                result.as_ref().err(),
+            );
+
+            match result {
+                Ok(parts) => parts,
+                Err(error::Error::Status(status)) => {
+                    warn!("multiple ranges refused with status {}", status);
+                    vec![]
+                }
+                Err(error) => return Err(error),
+            }
+        } else {
+            vec![]
+        };
+
+        ranges
+            .iter()
+            .map(|&(start, length)| {
+                if length == 0 {
+                    return Ok(vec![]);
+                }
+                let part = parts.iter().find(|part| {
+                    part.start <= start && start + length <= part.start + part.data.len() as u64
+                });
+                match part {
+                    Some(part) => {
+                        let offset = (start - part.start) as usize;
+                        Ok(part.data[offset..offset + length as usize].to_vec())
+                    }
+                    None => self.get_range(start, length),
+                }
+            })
+            .collect()
+    }
+
+    fn get_range(&self, start: u64, length: u64) -> Result<Vec<u8>, error::Error> {
+        let request_start = Instant::now();
+        let result = get_data(self, start, start + length - 1);
+        let size = match result {
+            Ok(ref response) => response.body_data.len() as u64,
+            Err(_) => 0,
+        };
+        notify_request(
+            self,
+            start,
+            size,
+            request_start.elapsed(),
+            result.as_ref().err(),
+        );
+        result.map(|response| response.body_data)
+    }
+
+    fn watch(&self) -> Watch<'_> {
+        Watch {
+            cancellation: &self.cancellation,
+            deadline: self.deadline,
+            progress: None,
         }
-        None => {
-            reader.position = new_position;
+    }
+
+    /// Client shared by the requests of the reader, built when opening it.
+    fn client(&self) -> Result<Client, error::Error> {
+        self.client
+            .clone()
+            .ok_or_else(|| error::Error::Request("reader is not open".to_string()))
+    }
+
+    /// Like `open`, keeping the typed error of the `HEAD` request.
+    pub fn try_open(&mut self, filename: &str) -> Result<(), error::Error> {
+        self.filename = filename.to_string();
+        let start = Instant::now();
+        self.deadline = self.timeouts.deadline_from(start);
+        self.size_discovery_failed = false;
+
+        self.client = None;
+        if self.http_version == HttpVersion::Http2PriorKnowledge && is_https(filename) {
+            return Err(error::Error::Unsupported(format!(
+                "HTTP/2 prior knowledge over HTTPS for {}",
+                filename
+            )));
         }
-    };
-    Ok(Some(response.body_data))
+        let result = build_client(&self.timeouts, self.http_version).and_then(|client| {
+            self.client = Some(client.clone());
+            get_head(&client, filename, &self.signer, &self.watch())
+        });
+        record_request(self, "HEAD", 0, 0, start.elapsed(), result.as_ref().err());
+
+        self.file_size = head_size(&result?);
+        Ok(())
+    }
+
+    /// Request the size again, for files still being written. An unknown
+    /// size keeps the previous one.
+    pub fn refresh_size(&mut self) -> Result<Option<u64>, error::Error> {
+        let start = Instant::now();
+        let result = self
+            .client()
+            .and_then(|client| get_head(&client, &self.filename, &self.signer, &self.watch()));
+        record_request(self, "HEAD", 0, 0, start.elapsed(), result.as_ref().err());
+        if let Some(file_size) = head_size(&result?) {
+            self.file_size = Some(file_size);
+        }
+        Ok(self.file_size)
+    }
+
+    /// Request the first byte to learn the size from its `Content-Range`, for
+    /// servers that do not announce it on `HEAD`.
+    fn discover_size(&mut self) -> Result<(), error::Error> {
+        let start = Instant::now();
+        let result = get_data(self, 0, 0);
+        let size = match result {
+            Ok(ref response) => response.body_data.len() as u64,
+            Err(_) => 0,
+        };
+        record_request(self, "GET", 0, size, start.elapsed(), result.as_ref().err());
+        if let Some(file_size) = result?.file_size {
+            self.file_size = Some(file_size);
+        }
+        Ok(())
+    }
+
+    /// Read up to `size` bytes at the cache position from the open-ended
+    /// request, sending it first if needed. The request is reopened after a
+    /// seek and dropped at the end of the body.
+    fn read_stream(&mut self, size: usize) -> Result<Option<Vec<u8>>, error::Error> {
+        let position = self.buffer.position;
+        let stream = match self.stream.get_mut().unwrap().take() {
+            Some(ref stream) if stream.position != position => None,
+            stream => stream,
+        };
+        let mut stream = match stream {
+            Some(stream) => stream,
+            None => match self.open_stream(position)? {
+                Some(stream) => stream,
+                None => return Ok(None),
+            },
+        };
+
+        // The origin waits while the caller is not reading, so the rate only
+        // applies to this call.
+        let watch = self
+            .watch()
+            .with_progress(&self.timeouts, Instant::now(), 0);
+        let data = match stream.response.read(&watch, size)? {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+        let readed_size = data.len();
+        if let Some(ref rate_limiter) = self.rate_limiter {
+            rate_limiter.acquire_with(readed_size as u64, &self.cancellation, self.deadline)?;
+        }
+
+        self.stats.bytes_fetched += readed_size as u64;
+        stream.position += readed_size as u64;
+        self.buffer.position = stream.position;
+        *self.stream.get_mut().unwrap() = Some(stream);
+        Ok(Some(data))
+    }
+
+    fn open_stream(&mut self, position: u64) -> Result<Option<Stream>, error::Error> {
+        let start = Instant::now();
+        let result = send_range_request(self, vec![AllFrom(position)]);
+        record_request(
+            self,
+            "GET",
+            position,
+            0,
+            start.elapsed(),
+            result.as_ref().err(),
+        );
+        let mut response = result?;
+
+        if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            if let Some(file_size) = unsatisfied_range_length(&response) {
+                self.file_size = Some(file_size);
+            }
+            return Ok(None);
+        }
+
+        let content_range = parse_content_range(&response)?;
+        if let Some(ContentRange {
+            length: Some(file_size),
+            ..
+        }) = content_range
+        {
+            self.file_size = Some(file_size);
+        }
+        match content_range {
+            Some(ContentRange {
+                range: Some((range_start, _)),
+                ..
+            }) if range_start == position => {}
+            None if response.status() == StatusCode::OK => {
+                // The server ignored the range, skip the beginning of the content.
+                let mut skipped = 0;
+                while skipped < position {
+                    let size = cmp::min(position - skipped, BODY_CHUNK_SIZE as u64) as usize;
+                    let watch = self.watch().with_progress(&self.timeouts, start, skipped);
+                    match response.read(&watch, size)? {
+                        Some(data) => skipped += data.len() as u64,
+                        None => return Ok(None),
+                    }
+                }
+            }
+            _ => {
+                return Err(error::Error::Other(format!(
+                    "requested range from {}, received {:?}",
+                    position, content_range
+                )));
+            }
+        }
+
+        Ok(Some(Stream { response, position }))
+    }
+
+    fn get_parts(
+        &self,
+        specs: Vec<ByteRangeSpec>,
+        start: Instant,
+    ) -> Result<Vec<BytePart>, error::Error> {
+        let end = specs
+            .iter()
+            .filter_map(|spec| match *spec {
+                FromTo(_, end) => Some(end.saturating_add(1)),
+                _ => None,
+            })
+            .max();
+        let mut response = send_range_request(self, specs)?;
+        if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            return Err(error::Error::Status(response.status().as_u16()));
+        }
+
+        if response.status() == StatusCode::OK {
+            // The ranges were ignored, only read the content up to the last one.
+            let body = read_body(self, &mut response, start, end)?;
+            if !is_truncated(&response, &body, end) {
+                verify_body(&response, &body)?;
+            }
+            return Ok(vec![BytePart {
+                start: 0,
+                data: body,
+            }]);
+        }
+
+        let body = read_body(self, &mut response, start, None)?;
+        let boundary = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(byteranges_boundary);
+        if let Some(boundary) = boundary {
+            return parse_byteranges(&body, &boundary).map_err(error::Error::Corrupted);
+        }
+
+        // The server coalesced the ranges into a single one.
+        verify_body(&response, &body)?;
+        let content_range = response
+            .headers()
+            .get(header::CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| ContentRangeSpec::from_str(value).ok());
+        match content_range {
+            Some(ContentRangeSpec::Bytes {
+                range: Some((range_start, _)),
+                ..
+            }) => Ok(vec![BytePart {
+                start: range_start,
+                data: body,
+            }]),
+            _ => Err("bad response header".to_string().into()),
+        }
+    }
 }
 
 impl Reader for HttpReader {
@@ -175,30 +1224,24 @@ impl Reader for HttpReader {
             filename: "".to_string(),
             file_size: None,
             position: 0,
-            buffer: Buffer {
-                size: None,
-                position: 0,
-                max_end_position: None,
-                buffer: vec![],
-            },
+            buffer: Buffer::create(),
+            signer: None,
+            timeouts: Timeouts::default(),
+            rate_limiter: None,
+            stats: TransferStats::default(),
+            metrics: None,
+            cancellation: CancellationToken::new(),
+            streaming: false,
+            http_version: HttpVersion::default(),
+            client: None,
+            stream: Mutex::new(None),
+            deadline: None,
+            size_discovery_failed: false,
         }
     }
 
     fn open(&mut self, filename: &str) -> Result<(), String> {
-        self.filename = filename.to_string();
-
-        match get_head(filename) {
-            Err(msg) => Err(msg.to_string()),
-            Ok(response) => {
-                let content_length = match get_content_range(&response) {
-                    Ok(length) => length,
-                    _ => response.content_length(),
-                };
-
-                self.file_size = content_length;
-                Ok(())
-            }
-        }
+        self.try_open(filename).map_err(String::from)
     }
 
     fn get_position(&mut self) -> Result<u64, String> {
@@ -222,6 +1265,12 @@ impl Reader for HttpReader {
     }
 
     fn get_size(&mut self) -> Result<u64, String> {
+        if self.file_size.is_none() && !self.streaming && !self.size_discovery_failed {
+            // Errors are returned without remembering them, to retry on the
+            // next call; only a response without a length is final.
+            self.discover_size()?;
+            self.size_discovery_failed = self.file_size.is_none();
+        }
         match self.file_size {
             Some(length) => Ok(length),
             None => Err("No length detected".to_string()),
@@ -231,9 +1280,22 @@ impl Reader for HttpReader {
 
 impl Read for HttpReader {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.cancellation.check()?;
+        if self.streaming {
+            let readed_size = {
+                let data = self.fill_buf()?;
+                let readed_size = cmp::min(data.len(), buf.len());
+                buf[..readed_size].clone_from_slice(&data[..readed_size]);
+                readed_size
+            };
+            self.consume(readed_size);
+            return Ok(readed_size);
+        }
         if self.buffer.get_cached_size() >= buf.len() {
             self.position += buf.len() as u64;
+            self.stats.cache_hits += 1;
             if self.buffer.get_data(buf) {
+                self.stats.bytes_delivered += buf.len() as u64;
                 Ok(buf.len())
             } else {
                 Err(Error::new(
@@ -242,30 +1304,32 @@ impl Read for HttpReader {
                 ))
             }
         } else if let Some(buffer_size) = self.buffer.size {
-            let some_data =
-                load_data(self, buffer_size).map_err(|msg| Error::new(ErrorKind::Other, msg))?;
-
-            if let Some(data) = some_data {
-                self.buffer.append_data(&data.to_vec());
-                self.position += buf.len() as u64;
-                if self.buffer.get_data(buf) {
-                    Ok(buf.len())
-                } else {
-                    Err(Error::new(
-                        ErrorKind::Other,
-                        "unable to read data from HTTP cache",
-                    ))
-                }
-            } else {
-                Ok(0)
+            self.stats.cache_misses += 1;
+            if let Some(data) = load_data(self, buffer_size, true).map_err(Error::from)? {
+                self.buffer.append_data(&data);
             }
+
+            // Near the end, less data than requested remains.
+            let size = cmp::min(buf.len(), self.buffer.get_cached_size());
+            self.buffer.get_data(&mut buf[..size]);
+            self.position += size as u64;
+            self.stats.bytes_delivered += size as u64;
+            Ok(size)
+        } else if self.buffer.get_cached_size() > 0 {
+            // Data left over by `fill_buf` on a reader without cache.
+            let size = self.buffer.get_cached_size();
+            self.buffer.get_data(&mut buf[..size]);
+            self.position += size as u64;
+            self.stats.cache_hits += 1;
+            self.stats.bytes_delivered += size as u64;
+            Ok(size)
         } else {
-            let some_data =
-                load_data(self, buf.len()).map_err(|msg| Error::new(ErrorKind::Other, msg))?;
+            let some_data = load_data(self, buf.len(), false).map_err(Error::from)?;
 
             if let Some(data) = some_data {
                 if data.len() >= buf.len() {
                     buf.clone_from_slice(&data);
+                    self.stats.bytes_delivered += data.len() as u64;
                     Ok(data.len())
                 } else {
                     Ok(0)
@@ -277,36 +1341,88 @@ impl Read for HttpReader {
     }
 }
 
+/// Serve the cache directly. Without a cache size, each `fill_buf` fetches
+/// up to 64 KiB into the buffer and leaves the cache size unset, so `Read`
+/// stays uncached once the buffered data is consumed.
+impl BufRead for HttpReader {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        self.cancellation.check()?;
+
+        let buffer_size = self.buffer.size.unwrap_or(DEFAULT_CACHE_SIZE);
+
+        if self.buffer.get_cached_size() == 0 {
+            if self.buffer.size.is_none() {
+                self.buffer.position = self.position;
+            }
+            self.stats.cache_misses += 1;
+            let data = if self.streaming {
+                self.read_stream(buffer_size)
+            } else {
+                load_data(self, buffer_size, true)
+            };
+            if let Some(data) = data.map_err(Error::from)? {
+                self.buffer.append_data(&data);
+            }
+        } else {
+            self.stats.cache_hits += 1;
+        }
+        Ok(self.buffer.get_cached_data())
+    }
+
+    fn consume(&mut self, amount: usize) {
+        let amount = cmp::min(amount, self.buffer.get_cached_size());
+        self.buffer.consume(amount);
+        self.position += amount as u64;
+        self.stats.bytes_delivered += amount as u64;
+    }
+}
+
+/// Each call sends its own range request, bypassing the cache. Requests are
+/// reported to the metrics hook but not counted in `stats`.
+impl ReadAt for HttpReader {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        self.cancellation.check()?;
+
+        let size = match self.file_size {
+            Some(file_size) if offset >= file_size => return Ok(0),
+            Some(file_size) => cmp::min(buf.len() as u64, file_size - offset) as usize,
+            None => buf.len(),
+        };
+        if size == 0 {
+            return Ok(0);
+        }
+
+        let data = self.get_range(offset, size as u64)?;
+        let readed_size = cmp::min(size, data.len());
+        buf[..readed_size].clone_from_slice(&data[..readed_size]);
+        Ok(readed_size)
+    }
+}
+
 impl Seek for HttpReader {
     fn seek(&mut self, seek_from: SeekFrom) -> Result<u64, Error> {
+        self.cancellation.check()?;
         match seek_from {
             SeekFrom::Current(offset) => {
                 self.position += offset as u64;
-                if self.buffer.size.is_some() {
-                    if offset > 0 && self.buffer.get_cached_size() > offset as usize {
-                        let mut skipped_data = vec![];
-                        skipped_data.resize(offset as usize, 0);
-                        let _skiped_data = self.buffer.get_data(&mut skipped_data);
-                    } else {
-                        self.buffer.reset();
-                    }
+                if offset > 0 && self.buffer.get_cached_size() > offset as usize {
+                    self.buffer.consume(offset as usize);
+                } else {
+                    self.buffer.reset();
+                    self.buffer.position = self.position;
                 }
             }
             SeekFrom::Start(offset) => {
                 self.buffer.reset();
                 self.position = offset;
-                if self.buffer.size.is_some() {
-                    self.buffer.position = self.position;
-                }
+                self.buffer.position = self.position;
             }
             SeekFrom::End(offset) => {
                 self.buffer.reset();
                 match self.file_size {
                     Some(size) => {
                         self.position = size - offset as u64;
-                        if self.buffer.size.is_some() {
-                            self.buffer.position = self.position;
-                        }
+                        self.buffer.position = self.position;
                     }
                     None => return Err(Error::new(ErrorKind::Other, "Missing file size")),
                 }
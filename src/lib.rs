@@ -1,24 +1,265 @@
 #![crate_name = "file_api"]
 #![crate_type = "lib"]
 
+extern crate base64;
+extern crate bytes;
+#[cfg(feature = "bzip2")]
+extern crate bzip2;
+#[cfg(feature = "gzip")]
+extern crate flate2;
+extern crate glob;
+extern crate hmac;
+extern crate httpdate;
 extern crate hyper;
 extern crate hyperx;
 #[macro_use]
+extern crate lazy_static;
+#[macro_use]
 extern crate log;
+extern crate md5;
+#[cfg(feature = "mmap")]
+extern crate memmap;
+#[cfg(feature = "service-account")]
+extern crate openssl;
+extern crate percent_encoding;
 extern crate reqwest;
+#[macro_use]
+extern crate serde_json;
+extern crate sha1;
+extern crate sha2;
+extern crate twox_hash;
+#[cfg(feature = "xz")]
+extern crate xz2;
+#[cfg(feature = "zstd")]
+extern crate zstd;
 
 pub mod buffer;
 
+pub mod archive_reader;
+pub mod azure;
+pub mod cancellation;
+pub mod checksum;
+pub mod concat_reader;
+pub mod data_reader;
+pub mod decompress_reader;
+pub mod error;
 pub mod file_reader;
+pub mod follow;
+pub mod gcs;
 pub mod http_reader;
+pub mod listing;
+pub mod memory_reader;
+pub mod metadata;
+pub mod multipart;
+pub mod progress;
+pub mod rate_limiter;
 pub mod reader;
+pub mod shared_reader;
+pub mod signer;
+pub mod slice_reader;
+pub mod stats;
+pub mod timeouts;
+
+use reader::{ReadAt, Reader};
+use std::io::{self, BufRead, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::sync::Arc;
+use std::time::Duration;
 
-use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+const CHECKSUM_CACHE_SIZE: usize = 1024 * 1024;
 
 #[derive(Debug)]
 pub struct MainReader {
     pub http_reader: Option<http_reader::HttpReader>,
     pub file_reader: Option<file_reader::FileReader>,
+    pub memory_reader: Option<memory_reader::MemoryReader>,
+    pub archive_reader: Option<Box<slice_reader::SliceReader<MainReader>>>,
+    pub concat_reader: Option<concat_reader::ConcatReader>,
+    pub data_reader: Option<data_reader::DataReader>,
+    pub decompress_reader: Option<decompress_reader::DecompressReader<MainReader>>,
+    pub timeouts: timeouts::Timeouts,
+    pub rate_limiter: Option<Arc<rate_limiter::RateLimiter>>,
+    pub metrics: Option<Arc<dyn stats::Metrics>>,
+    pub progress: Option<progress::ProgressReporter>,
+    pub follow: Option<follow::Follow>,
+    pub cancellation: cancellation::CancellationToken,
+    pub use_mmap: bool,
+    pub streaming: bool,
+    pub http_version: http_reader::HttpVersion,
+    pub decompress: bool,
+}
+
+impl MainReader {
+    pub fn get_media_type(&self) -> Option<&str> {
+        if let Some(ref reader) = self.data_reader {
+            return Some(reader.get_media_type());
+        }
+        None
+    }
+
+    /// Time limits used by remote readers opened afterwards.
+    pub fn set_timeouts(&mut self, timeouts: timeouts::Timeouts) {
+        self.timeouts = timeouts;
+    }
+
+    /// Bandwidth budget shared by remote readers opened afterwards.
+    pub fn set_rate_limiter(&mut self, rate_limiter: Option<Arc<rate_limiter::RateLimiter>>) {
+        self.rate_limiter = rate_limiter.clone();
+        if let Some(ref mut reader) = self.http_reader {
+            reader.rate_limiter = rate_limiter;
+        }
+    }
+
+    /// Hook called for every request of remote readers opened afterwards.
+    pub fn set_metrics(&mut self, metrics: Option<Arc<dyn stats::Metrics>>) {
+        self.metrics = metrics.clone();
+        if let Some(ref mut reader) = self.http_reader {
+            reader.metrics = metrics;
+        }
+    }
+
+    /// Transfer counters of the underlying reader, summed over the segments
+    /// of a concatenation.
+    pub fn get_stats(&self) -> Option<stats::TransferStats> {
+        if let Some(ref reader) = self.http_reader {
+            return Some(reader.stats.clone());
+        }
+        if let Some(ref reader) = self.file_reader {
+            return Some(reader.stats.clone());
+        }
+        if let Some(ref reader) = self.memory_reader {
+            return Some(reader.stats.clone());
+        }
+        if let Some(ref reader) = self.archive_reader {
+            return reader.reader.get_stats();
+        }
+        if let Some(ref reader) = self.concat_reader {
+            return Some(reader.get_stats());
+        }
+        if let Some(ref reader) = self.data_reader {
+            return Some(reader.reader.stats.clone());
+        }
+        if let Some(ref reader) = self.decompress_reader {
+            return reader.get_stats();
+        }
+        None
+    }
+
+    /// Notify `observer` of the read progress at most once per `interval`.
+    pub fn set_progress_observer(
+        &mut self,
+        observer: Arc<dyn progress::ProgressObserver>,
+        interval: Duration,
+    ) {
+        self.progress = Some(progress::ProgressReporter::new(observer, interval));
+    }
+
+    /// Protocol of HTTP requests. With HTTP/2, the requests of a reader,
+    /// including `read_at` calls from several threads, share one connection.
+    /// HTTP/2 is only spoken to plain `http://` origins, opening an
+    /// `https://` URL fails.
+    pub fn set_http_version(&mut self, http_version: http_reader::HttpVersion) {
+        self.http_version = http_version;
+    }
+
+    /// Wait for local and HTTP files to grow when reaching their end, until
+    /// `follow` is complete or idle.
+    pub fn set_follow(&mut self, follow: Option<follow::Follow>) {
+        self.follow = follow;
+    }
+
+    /// Handle to abort the reads of this reader from another thread.
+    pub fn cancellation_token(&self) -> cancellation::CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Map local files in memory when they are opened afterwards.
+    pub fn set_mmap(&mut self, use_mmap: bool) {
+        self.use_mmap = use_mmap;
+    }
+
+    /// Read HTTP resources with a single open-ended request, for contents
+    /// whose size is unknown such as live recordings.
+    pub fn set_streaming(&mut self, streaming: bool) {
+        self.streaming = streaming;
+    }
+
+    /// Decompress gzip, zstd, xz and bzip2 files opened afterwards, detected
+    /// from their extension or their first bytes. Other files are read as is.
+    pub fn set_decompress(&mut self, decompress: bool) {
+        self.decompress = decompress;
+    }
+
+    /// Return the metadata of `filename` like `stat`, checking remote files
+    /// with the timeouts and the settings of this reader.
+    pub fn stat(&self, filename: &str) -> Result<Option<metadata::Metadata>, error::Error> {
+        match detect_kind(filename) {
+            ReaderKind::Archive => archive_reader::stat_with(filename, self.child()),
+            ReaderKind::Http => http_reader::stat(filename, &None, &self.timeouts),
+            ReaderKind::Azure => {
+                let (url, signer) = azure::resolve(filename)?;
+                http_reader::stat(&url, &Some(signer), &self.timeouts)
+            }
+            ReaderKind::Gcs => {
+                let (url, signer) = gcs::resolve(filename)?;
+                http_reader::stat(&url, &Some(signer), &self.timeouts)
+            }
+            ReaderKind::Memory => Ok(memory_reader::stat(filename)),
+            ReaderKind::Concat => concat_reader::stat_with(filename, self),
+            ReaderKind::Data => {
+                let (media_type, data) = data_reader::decode(filename)?;
+                Ok(Some(metadata::Metadata {
+                    size: Some(data.len() as u64),
+                    content_type: Some(media_type),
+                    ..Default::default()
+                }))
+            }
+            ReaderKind::File => file_reader::stat(filename),
+        }
+    }
+
+    /// Reader of an archive or a segment, sharing the settings and the
+    /// cancellation token of this one.
+    fn child(&self) -> MainReader {
+        let mut reader = MainReader::new();
+        reader.timeouts = self.timeouts.clone();
+        reader.rate_limiter = self.rate_limiter.clone();
+        reader.metrics = self.metrics.clone();
+        reader.cancellation = self.cancellation.clone();
+        reader.use_mmap = self.use_mmap;
+        reader.http_version = self.http_version;
+        reader
+    }
+
+    /// Like `open`, keeping the typed error of remote files.
+    fn try_open(&mut self, filename: &str) -> Result<(), error::Error> {
+        match detect_kind(filename) {
+            ReaderKind::Http => self.open_http(filename, None),
+            ReaderKind::Azure => {
+                let (url, signer) = azure::resolve(filename)?;
+                self.open_http(&url, Some(signer))
+            }
+            ReaderKind::Gcs => {
+                let (url, signer) = gcs::resolve(filename)?;
+                self.open_http(&url, Some(signer))
+            }
+            _ => Ok(self.open(filename)?),
+        }
+    }
+
+    fn open_http(&mut self, url: &str, signer: Option<signer::Signer>) -> Result<(), error::Error> {
+        let mut reader = http_reader::HttpReader::new();
+        reader.signer = signer;
+        reader.timeouts = self.timeouts.clone();
+        reader.rate_limiter = self.rate_limiter.clone();
+        reader.metrics = self.metrics.clone();
+        reader.cancellation = self.cancellation.clone();
+        reader.streaming = self.streaming;
+        reader.http_version = self.http_version;
+
+        reader.try_open(url)?;
+        self.http_reader = Some(reader);
+        Ok(())
+    }
 }
 
 impl reader::Reader for MainReader {
@@ -26,17 +267,76 @@ impl reader::Reader for MainReader {
         MainReader {
             http_reader: None,
             file_reader: None,
+            memory_reader: None,
+            archive_reader: None,
+            concat_reader: None,
+            data_reader: None,
+            decompress_reader: None,
+            timeouts: timeouts::Timeouts::default(),
+            rate_limiter: None,
+            metrics: None,
+            progress: None,
+            follow: None,
+            cancellation: cancellation::CancellationToken::new(),
+            use_mmap: false,
+            streaming: false,
+            http_version: http_reader::HttpVersion::default(),
+            decompress: false,
         }
     }
 
     fn open(&mut self, filename: &str) -> Result<(), String> {
+        if let Some(ref mut progress) = self.progress {
+            progress.reset_size();
+        }
+        if self.decompress {
+            let mut reader = self.child();
+            reader.streaming = self.streaming;
+            if let Some(reader) = decompress_reader::open_with(filename, reader)? {
+                self.decompress_reader = Some(reader);
+                return Ok(());
+            }
+            // Content that is not compressed is opened again and read as is.
+        }
+
         match detect_kind(filename) {
-            ReaderKind::Http => {
-                let mut reader = http_reader::HttpReader::new();
+            ReaderKind::Http | ReaderKind::Azure | ReaderKind::Gcs => {
+                self.try_open(filename).map_err(String::from)
+            }
+            ReaderKind::Memory => {
+                let mut reader = memory_reader::MemoryReader::new();
 
                 match reader.open(filename) {
                     Ok(()) => {
-                        self.http_reader = Some(reader);
+                        self.memory_reader = Some(reader);
+                        Ok(())
+                    }
+                    Err(msg) => Err(msg),
+                }
+            }
+            ReaderKind::Archive => {
+                let reader = archive_reader::open_member_with(filename, self.child())?;
+                self.archive_reader = Some(Box::new(reader));
+                Ok(())
+            }
+            ReaderKind::Concat => {
+                let mut reader = concat_reader::ConcatReader::new();
+                let segments = concat_reader::resolve_segments(filename);
+
+                match reader.open_segments_with(&segments, || self.child()) {
+                    Ok(()) => {
+                        self.concat_reader = Some(reader);
+                        Ok(())
+                    }
+                    Err(msg) => Err(msg),
+                }
+            }
+            ReaderKind::Data => {
+                let mut reader = data_reader::DataReader::new();
+
+                match reader.open(filename) {
+                    Ok(()) => {
+                        self.data_reader = Some(reader);
                         Ok(())
                     }
                     Err(msg) => Err(msg),
@@ -44,6 +344,7 @@ impl reader::Reader for MainReader {
             }
             ReaderKind::File => {
                 let mut reader = file_reader::FileReader::new();
+                reader.use_mmap = self.use_mmap;
 
                 match reader.open(filename) {
                     Ok(()) => {
@@ -63,6 +364,21 @@ impl reader::Reader for MainReader {
         if let Some(ref reader) = self.file_reader {
             return reader.get_cache_size();
         }
+        if let Some(ref reader) = self.memory_reader {
+            return reader.get_cache_size();
+        }
+        if let Some(ref reader) = self.archive_reader {
+            return reader.get_cache_size();
+        }
+        if let Some(ref reader) = self.concat_reader {
+            return reader.get_cache_size();
+        }
+        if let Some(ref reader) = self.data_reader {
+            return reader.get_cache_size();
+        }
+        if let Some(ref reader) = self.decompress_reader {
+            return reader.get_cache_size();
+        }
         None
     }
 
@@ -73,6 +389,21 @@ impl reader::Reader for MainReader {
         if let Some(ref mut reader) = self.file_reader {
             reader.set_cache_size(cache_size)
         }
+        if let Some(ref mut reader) = self.memory_reader {
+            reader.set_cache_size(cache_size)
+        }
+        if let Some(ref mut reader) = self.archive_reader {
+            reader.set_cache_size(cache_size)
+        }
+        if let Some(ref mut reader) = self.concat_reader {
+            reader.set_cache_size(cache_size)
+        }
+        if let Some(ref mut reader) = self.data_reader {
+            reader.set_cache_size(cache_size)
+        }
+        if let Some(ref mut reader) = self.decompress_reader {
+            reader.set_cache_size(cache_size)
+        }
     }
 
     fn get_max_end_position(&self) -> Option<u64> {
@@ -82,6 +413,21 @@ impl reader::Reader for MainReader {
         if let Some(ref reader) = self.file_reader {
             return reader.get_max_end_position();
         }
+        if let Some(ref reader) = self.memory_reader {
+            return reader.get_max_end_position();
+        }
+        if let Some(ref reader) = self.archive_reader {
+            return reader.get_max_end_position();
+        }
+        if let Some(ref reader) = self.concat_reader {
+            return reader.get_max_end_position();
+        }
+        if let Some(ref reader) = self.data_reader {
+            return reader.get_max_end_position();
+        }
+        if let Some(ref reader) = self.decompress_reader {
+            return reader.get_max_end_position();
+        }
         None
     }
 
@@ -92,6 +438,21 @@ impl reader::Reader for MainReader {
         if let Some(ref mut reader) = self.file_reader {
             return reader.set_max_end_position(max_end_position);
         }
+        if let Some(ref mut reader) = self.memory_reader {
+            return reader.set_max_end_position(max_end_position);
+        }
+        if let Some(ref mut reader) = self.archive_reader {
+            return reader.set_max_end_position(max_end_position);
+        }
+        if let Some(ref mut reader) = self.concat_reader {
+            return reader.set_max_end_position(max_end_position);
+        }
+        if let Some(ref mut reader) = self.data_reader {
+            return reader.set_max_end_position(max_end_position);
+        }
+        if let Some(ref mut reader) = self.decompress_reader {
+            reader.set_max_end_position(max_end_position);
+        }
     }
 
     fn get_position(&mut self) -> Result<u64, String> {
@@ -101,6 +462,21 @@ impl reader::Reader for MainReader {
         if let Some(ref mut reader) = self.file_reader {
             return reader.get_position();
         }
+        if let Some(ref mut reader) = self.memory_reader {
+            return reader.get_position();
+        }
+        if let Some(ref mut reader) = self.archive_reader {
+            return reader.get_position();
+        }
+        if let Some(ref mut reader) = self.concat_reader {
+            return reader.get_position();
+        }
+        if let Some(ref mut reader) = self.data_reader {
+            return reader.get_position();
+        }
+        if let Some(ref mut reader) = self.decompress_reader {
+            return Ok(reader.get_position());
+        }
         Err("no reader configured".to_string())
     }
 
@@ -111,54 +487,368 @@ impl reader::Reader for MainReader {
         if let Some(ref mut reader) = self.file_reader {
             return reader.get_size();
         }
+        if let Some(ref mut reader) = self.memory_reader {
+            return reader.get_size();
+        }
+        if let Some(ref mut reader) = self.archive_reader {
+            return reader.get_size();
+        }
+        if let Some(ref mut reader) = self.concat_reader {
+            return reader.get_size();
+        }
+        if let Some(ref mut reader) = self.data_reader {
+            return reader.get_size();
+        }
+        if let Some(ref mut reader) = self.decompress_reader {
+            return reader.get_size();
+        }
         Err("no reader configured".to_string())
     }
 }
 
-impl Read for MainReader {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+impl MainReader {
+    /// Update the size of a followed file from the file system or the server.
+    fn refresh_size(&mut self) -> Result<(), error::Error> {
+        if let Some(ref mut reader) = self.http_reader {
+            reader.refresh_size()?;
+        }
+        if let Some(ref mut reader) = self.file_reader {
+            reader.refresh_size()?;
+        }
+        Ok(())
+    }
+
+    /// In follow mode, call `read` each time the size is refreshed until it
+    /// returns true or the follow ends.
+    fn wait_for_growth<F>(&mut self, mut read: F) -> Result<(), Error>
+    where
+        F: FnMut(&mut MainReader) -> Result<bool, Error>,
+    {
+        let follow = match self.follow {
+            Some(ref follow) => follow.clone(),
+            None => return Ok(()),
+        };
+        let cancellation = self.cancellation.clone();
+        follow.wait(&cancellation, || {
+            self.refresh_size()?;
+            Ok(read(self)?)
+        })?;
+        Ok(())
+    }
+
+    fn fill_data(&mut self) -> Result<&[u8], Error> {
+        if let Some(ref mut reader) = self.http_reader {
+            return reader.fill_buf();
+        }
+        if let Some(ref mut reader) = self.file_reader {
+            return reader.fill_buf();
+        }
+        if let Some(ref mut reader) = self.memory_reader {
+            return reader.fill_buf();
+        }
+        if let Some(ref mut reader) = self.archive_reader {
+            return reader.fill_buf();
+        }
+        if let Some(ref mut reader) = self.concat_reader {
+            return reader.fill_buf();
+        }
+        if let Some(ref mut reader) = self.data_reader {
+            return reader.fill_buf();
+        }
+        if let Some(ref mut reader) = self.decompress_reader {
+            return reader.fill_buf();
+        }
+        Err(Error::new(ErrorKind::Other, "no reader configured"))
+    }
+
+    fn report_progress(&mut self) {
+        let needs_size = match self.progress {
+            Some(ref progress) => progress.needs_size(),
+            None => return,
+        };
+        // The size may cost a request on HTTP readers, so it is looked up once.
+        let size = if needs_size {
+            self.get_size().ok()
+        } else {
+            None
+        };
+        let position = self.get_position().unwrap_or(0);
+        if let Some(ref mut progress) = self.progress {
+            if needs_size {
+                progress.set_size(size);
+            }
+            progress.report(position);
+        }
+    }
+
+    fn read_data(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
         if let Some(ref mut reader) = self.http_reader {
             return reader.read(buf);
         }
         if let Some(ref mut reader) = self.file_reader {
             return reader.read(buf);
         }
+        if let Some(ref mut reader) = self.memory_reader {
+            return reader.read(buf);
+        }
+        if let Some(ref mut reader) = self.archive_reader {
+            return reader.read(buf);
+        }
+        if let Some(ref mut reader) = self.concat_reader {
+            return reader.read(buf);
+        }
+        if let Some(ref mut reader) = self.data_reader {
+            return reader.read(buf);
+        }
+        if let Some(ref mut reader) = self.decompress_reader {
+            return reader.read(buf);
+        }
+        Err(Error::new(ErrorKind::Other, "no reader configured"))
+    }
+}
+
+impl Read for MainReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.cancellation.check()?;
+        let mut readed_size = self.read_data(buf)?;
+        if readed_size == 0 && !buf.is_empty() {
+            self.wait_for_growth(|reader| {
+                readed_size = reader.read_data(buf)?;
+                Ok(readed_size > 0)
+            })?;
+        }
+
+        let report = match self.progress {
+            Some(ref mut progress) => progress.advance(readed_size),
+            None => false,
+        };
+        if report {
+            self.report_progress();
+        }
+        Ok(readed_size)
+    }
+}
+
+impl BufRead for MainReader {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        self.cancellation.check()?;
+
+        if self.follow.is_some() && self.fill_data()?.is_empty() {
+            self.wait_for_growth(|reader| Ok(!reader.fill_data()?.is_empty()))?;
+        }
+        self.fill_data()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        if let Some(ref mut reader) = self.http_reader {
+            reader.consume(amount);
+        }
+        if let Some(ref mut reader) = self.file_reader {
+            reader.consume(amount);
+        }
+        if let Some(ref mut reader) = self.memory_reader {
+            reader.consume(amount);
+        }
+        if let Some(ref mut reader) = self.archive_reader {
+            reader.consume(amount);
+        }
+        if let Some(ref mut reader) = self.concat_reader {
+            reader.consume(amount);
+        }
+        if let Some(ref mut reader) = self.data_reader {
+            reader.consume(amount);
+        }
+        if let Some(ref mut reader) = self.decompress_reader {
+            reader.consume(amount);
+        }
+
+        let report = match self.progress {
+            Some(ref mut progress) => progress.advance(amount),
+            None => false,
+        };
+        if report {
+            self.report_progress();
+        }
+    }
+}
+
+impl ReadAt for MainReader {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        self.cancellation.check()?;
+
+        if let Some(ref reader) = self.http_reader {
+            return reader.read_at(offset, buf);
+        }
+        if let Some(ref reader) = self.file_reader {
+            return reader.read_at(offset, buf);
+        }
+        if let Some(ref reader) = self.memory_reader {
+            return reader.read_at(offset, buf);
+        }
+        if let Some(ref reader) = self.archive_reader {
+            return reader.read_at(offset, buf);
+        }
+        if let Some(ref reader) = self.concat_reader {
+            return reader.read_at(offset, buf);
+        }
+        if let Some(ref reader) = self.data_reader {
+            return reader.read_at(offset, buf);
+        }
+        if let Some(ref reader) = self.decompress_reader {
+            return reader.read_at(offset, buf);
+        }
         Err(Error::new(ErrorKind::Other, "no reader configured"))
     }
 }
 
 impl Seek for MainReader {
     fn seek(&mut self, seek_from: SeekFrom) -> Result<u64, Error> {
+        self.cancellation.check()?;
         if let Some(ref mut reader) = self.http_reader {
             return reader.seek(seek_from);
         }
         if let Some(ref mut reader) = self.file_reader {
             return reader.seek(seek_from);
         }
+        if let Some(ref mut reader) = self.memory_reader {
+            return reader.seek(seek_from);
+        }
+        if let Some(ref mut reader) = self.archive_reader {
+            return reader.seek(seek_from);
+        }
+        if let Some(ref mut reader) = self.concat_reader {
+            return reader.seek(seek_from);
+        }
+        if let Some(ref mut reader) = self.data_reader {
+            return reader.seek(seek_from);
+        }
+        if let Some(ref mut reader) = self.decompress_reader {
+            return reader.seek(seek_from);
+        }
         Err(Error::new(ErrorKind::Other, "no reader configured"))
     }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 enum ReaderKind {
+    Archive,
     Http,
+    Azure,
+    Gcs,
+    Memory,
+    Concat,
+    Data,
     File,
 }
 
 fn detect_kind(filename: &str) -> ReaderKind {
+    if archive_reader::split_member(filename).is_some() {
+        return ReaderKind::Archive;
+    }
     if filename.starts_with("http://") || filename.starts_with("https://") {
         return ReaderKind::Http;
     }
+    if filename.starts_with("az://") {
+        return ReaderKind::Azure;
+    }
+    if filename.starts_with("gs://") {
+        return ReaderKind::Gcs;
+    }
+    if filename.starts_with("mem://") {
+        return ReaderKind::Memory;
+    }
+    if filename.starts_with("concat:") {
+        return ReaderKind::Concat;
+    }
+    if filename.starts_with("data:") {
+        return ReaderKind::Data;
+    }
     ReaderKind::File
 }
 
 pub fn exists(filename: &str) -> bool {
     match detect_kind(filename) {
+        ReaderKind::Archive => archive_reader::exists(filename),
         ReaderKind::Http => http_reader::exists(filename),
+        ReaderKind::Azure => match azure::resolve(filename) {
+            Ok((url, signer)) => http_reader::exists_with_signer(&url, &Some(signer)),
+            Err(_msg) => false,
+        },
+        ReaderKind::Gcs => match gcs::resolve(filename) {
+            Ok((url, signer)) => http_reader::exists_with_signer(&url, &Some(signer)),
+            Err(_msg) => false,
+        },
+        ReaderKind::Memory => memory_reader::exists(filename),
+        ReaderKind::Concat => concat_reader::exists(filename),
+        ReaderKind::Data => data_reader::decode(filename).is_ok(),
         ReaderKind::File => file_reader::exists(filename),
     }
 }
 
+/// Return the metadata of `filename`, `None` when it does not exist, or an error
+/// when its existence could not be checked.
+pub fn stat(filename: &str) -> Result<Option<metadata::Metadata>, error::Error> {
+    MainReader::new().stat(filename)
+}
+
+/// Read `filename` to the end and return its digests for every algorithm.
+pub fn checksum(
+    filename: &str,
+    algorithms: &[checksum::Algorithm],
+) -> Result<Vec<checksum::Checksum>, error::Error> {
+    checksum_with(filename, algorithms, MainReader::new())
+}
+
+/// Like `checksum`, reading `filename` through `reader` so that its timeouts,
+/// rate limiter or decompression apply.
+pub fn checksum_with(
+    filename: &str,
+    algorithms: &[checksum::Algorithm],
+    mut reader: MainReader,
+) -> Result<Vec<checksum::Checksum>, error::Error> {
+    reader.open(filename)?;
+    if reader.get_cache_size().is_none() {
+        reader.set_cache_size(Some(CHECKSUM_CACHE_SIZE));
+    }
+
+    let mut reader = checksum::ChecksumReader::create(reader, algorithms);
+    io::copy(&mut reader, &mut io::sink())?;
+    reader.checksums()
+}
+
+/// Read `filename` to the end and compare its digest with `expected`.
+pub fn verify_checksum(filename: &str, expected: &checksum::Checksum) -> Result<(), error::Error> {
+    verify_checksum_with(filename, expected, MainReader::new())
+}
+
+/// Like `verify_checksum`, reading `filename` through `reader`.
+pub fn verify_checksum_with(
+    filename: &str,
+    expected: &checksum::Checksum,
+    reader: MainReader,
+) -> Result<(), error::Error> {
+    let checksums = checksum_with(filename, &[expected.algorithm], reader)?;
+    checksum::verify(&checksums, expected)
+}
+
+/// List the entries of a local directory, an HTTP index or WebDAV collection,
+/// an object-store prefix or the `mem://` registry.
+pub fn list(filename: &str) -> Result<Vec<listing::Entry>, String> {
+    match detect_kind(filename) {
+        ReaderKind::Http => http_reader::list(filename),
+        ReaderKind::Azure => azure::list(filename),
+        ReaderKind::Gcs => gcs::list(filename),
+        ReaderKind::Memory => Ok(memory_reader::list(filename)),
+        ReaderKind::File => file_reader::list(filename),
+        _ => Err(format!("Listing is not supported for {}", filename)),
+    }
+}
+
+/// List entries whose name matches the glob `pattern`.
+pub fn list_glob(filename: &str, pattern: &str) -> Result<Vec<listing::Entry>, String> {
+    listing::filter(list(filename)?, pattern)
+}
+
 #[test]
 fn detect_file_kind() {
     let filename = "filename.txt".to_string();
@@ -176,3 +866,42 @@ fn detect_http_kind() {
     let kind_https = detect_kind(&filename_https);
     assert_eq!(kind_https, ReaderKind::Http);
 }
+
+#[test]
+fn detect_cloud_kind() {
+    let kind = detect_kind("az://container/filename.txt");
+    assert_eq!(kind, ReaderKind::Azure);
+
+    let kind = detect_kind("gs://bucket/filename.txt");
+    assert_eq!(kind, ReaderKind::Gcs);
+}
+
+#[test]
+fn detect_memory_kind() {
+    let kind = detect_kind("mem://filename.txt");
+    assert_eq!(kind, ReaderKind::Memory);
+}
+
+#[test]
+fn detect_data_kind() {
+    let kind = detect_kind("data:text/plain;base64,c29tZWRhdGE=");
+    assert_eq!(kind, ReaderKind::Data);
+}
+
+#[test]
+fn detect_concat_kind() {
+    let kind = detect_kind("concat:file.part001|http://source/file.part002");
+    assert_eq!(kind, ReaderKind::Concat);
+}
+
+#[test]
+fn detect_archive_kind() {
+    let kind = detect_kind("http://source/archive.zip#path/inside.mxf");
+    assert_eq!(kind, ReaderKind::Archive);
+
+    let kind = detect_kind("archive.tar#inside.mxf");
+    assert_eq!(kind, ReaderKind::Archive);
+
+    let kind = detect_kind("http://source/page.html#anchor");
+    assert_eq!(kind, ReaderKind::Http);
+}
@@ -0,0 +1,219 @@
+use glob::Pattern;
+use percent_encoding::percent_decode;
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// An item returned when listing a directory or an object-store prefix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub size: Option<u64>,
+    pub modified: Option<SystemTime>,
+    pub is_dir: bool,
+}
+
+/// Keep entries whose name matches the glob `pattern` (e.g. `*.mxf`).
+pub fn filter(entries: Vec<Entry>, pattern: &str) -> Result<Vec<Entry>, String> {
+    let pattern = Pattern::new(pattern).map_err(|msg| msg.to_string())?;
+    Ok(entries
+        .into_iter()
+        .filter(|entry| pattern.matches(&entry.name))
+        .collect())
+}
+
+/// Return the content of every `name` element, ignoring any namespace prefix.
+pub fn xml_elements<'a>(body: &'a str, name: &str) -> Vec<&'a str> {
+    let mut elements = vec![];
+    let mut position = 0;
+
+    while let Some(start) = body[position..].find('<') {
+        let tag_start = position + start + 1;
+        let tag_end = match body[tag_start..].find('>') {
+            Some(end) => tag_start + end,
+            None => break,
+        };
+        let tag = &body[tag_start..tag_end];
+        position = tag_end + 1;
+
+        if tag.starts_with('/') || tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        let tag_name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        let local_name = tag_name.rsplit(':').next().unwrap_or("");
+        if local_name != name {
+            continue;
+        }
+
+        if tag.ends_with('/') {
+            elements.push("");
+            continue;
+        }
+
+        let closing_tag = format!("</{}>", tag_name);
+        match body[position..].find(&closing_tag) {
+            Some(end) => {
+                elements.push(&body[position..position + end]);
+                position += end + closing_tag.len();
+            }
+            None => break,
+        }
+    }
+
+    elements
+}
+
+pub fn xml_element<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    xml_elements(body, name).into_iter().next()
+}
+
+pub fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Extract the links of an HTML index page (as generated by Apache or Nginx autoindex).
+pub fn parse_html_index(body: &str) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = vec![];
+
+    for part in body.split("href=").skip(1) {
+        let quote = match part.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => continue,
+        };
+        let href = match part[1..].find(quote) {
+            Some(end) => &part[1..=end],
+            None => continue,
+        };
+
+        if href.is_empty()
+            || href.starts_with('?')
+            || href.starts_with('#')
+            || href.starts_with("../")
+            || href.starts_with('/')
+            || href.contains("://")
+        {
+            continue;
+        }
+
+        let is_dir = href.ends_with('/');
+        let name = decode_name(href.trim_end_matches('/'));
+        if name.is_empty() || name == "." || name.contains('/') {
+            continue;
+        }
+
+        if !entries.iter().any(|entry| entry.name == name) {
+            entries.push(Entry {
+                name,
+                size: None,
+                modified: None,
+                is_dir,
+            });
+        }
+    }
+
+    entries
+}
+
+/// Parse a WebDAV `207 Multi-Status` PROPFIND response, skipping the listed collection itself.
+pub fn parse_propfind(body: &str, directory_path: &str) -> Vec<Entry> {
+    let directory_path = directory_path.trim_end_matches('/');
+    let mut entries = vec![];
+
+    for response in xml_elements(body, "response") {
+        let href = match xml_element(response, "href") {
+            Some(href) => xml_unescape(href.trim()),
+            None => continue,
+        };
+
+        let path = match href.find("://") {
+            Some(scheme_end) => match href[scheme_end + 3..].find('/') {
+                Some(path_start) => href[scheme_end + 3 + path_start..].to_string(),
+                None => "/".to_string(),
+            },
+            None => href,
+        };
+        let path = path.trim_end_matches('/');
+        if decode_name(path) == decode_name(directory_path) {
+            continue;
+        }
+
+        let name = decode_name(path.rsplit('/').next().unwrap_or(""));
+        if name.is_empty() {
+            continue;
+        }
+
+        let is_dir = match xml_element(response, "resourcetype") {
+            Some(resource_type) => !xml_elements(resource_type, "collection").is_empty(),
+            None => false,
+        };
+        let size =
+            xml_element(response, "getcontentlength").and_then(|size| size.trim().parse().ok());
+        let modified = xml_element(response, "getlastmodified")
+            .and_then(|date| ::httpdate::parse_http_date(date.trim()).ok());
+
+        entries.push(Entry {
+            name,
+            size,
+            modified,
+            is_dir,
+        });
+    }
+
+    entries
+}
+
+/// Name of a listed object relative to the listing `prefix`.
+pub fn relative_name<'a>(name: &'a str, prefix: &str) -> &'a str {
+    if name.starts_with(prefix) {
+        &name[prefix.len()..]
+    } else {
+        name
+    }
+}
+
+pub fn decode_name(name: &str) -> String {
+    percent_decode(name.as_bytes())
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+/// Parse a `YYYY-MM-DDTHH:MM:SS[.fff]Z` timestamp as returned by object stores.
+pub fn parse_iso8601(value: &str) -> Option<SystemTime> {
+    let value = value.trim().trim_end_matches('Z');
+    let (date, time) = {
+        let mut parts = value.splitn(2, 'T');
+        (parts.next()?, parts.next()?)
+    };
+
+    let mut date_parts = date.split('-').map(|part| part.parse::<i64>());
+    let year = date_parts.next()?.ok()?;
+    let month = date_parts.next()?.ok()?;
+    let day = date_parts.next()?.ok()?;
+
+    let time = time.split('.').next()?;
+    let mut time_parts = time.split(':').map(|part| part.parse::<u64>());
+    let hours = time_parts.next()?.ok()?;
+    let minutes = time_parts.next()?.ok()?;
+    let seconds = time_parts.next()?.ok()?;
+
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    if days < 0 {
+        return None;
+    }
+
+    let seconds = days as u64 * 86_400 + hours * 3_600 + minutes * 60 + seconds;
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
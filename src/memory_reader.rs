@@ -0,0 +1,233 @@
+use std::cmp;
+use std::collections::HashMap;
+use std::io::{BufRead, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::sync::Mutex;
+
+use buffer::Buffer;
+use bytes::Bytes;
+use listing::Entry;
+use metadata::Metadata;
+use reader::{ReadAt, Reader};
+use stats::TransferStats;
+
+lazy_static! {
+    static ref REGISTRY: Mutex<HashMap<String, Bytes>> = Mutex::new(HashMap::new());
+}
+
+/// Make `data` (a `Vec<u8>` or `Bytes`) available to readers under the
+/// `mem://` URL `filename`.
+pub fn register<D: Into<Bytes>>(filename: &str, data: D) {
+    let mut registry = REGISTRY.lock().unwrap();
+    registry.insert(filename.to_string(), data.into());
+}
+
+pub fn unregister(filename: &str) -> bool {
+    let mut registry = REGISTRY.lock().unwrap();
+    registry.remove(filename).is_some()
+}
+
+pub fn exists(filename: &str) -> bool {
+    let registry = REGISTRY.lock().unwrap();
+    registry.contains_key(filename)
+}
+
+pub fn stat(filename: &str) -> Option<Metadata> {
+    let registry = REGISTRY.lock().unwrap();
+    registry.get(filename).map(|data| Metadata {
+        size: Some(data.len() as u64),
+        ..Default::default()
+    })
+}
+
+/// List registered data under `prefix`, collapsing deeper paths into directories.
+pub fn list(prefix: &str) -> Vec<Entry> {
+    let prefix = if prefix.ends_with('/') {
+        prefix.to_string()
+    } else {
+        format!("{}/", prefix)
+    };
+
+    let registry = REGISTRY.lock().unwrap();
+    let mut entries: Vec<Entry> = vec![];
+
+    for (filename, data) in registry.iter() {
+        if !filename.starts_with(&prefix) || filename.len() == prefix.len() {
+            continue;
+        }
+
+        let name = &filename[prefix.len()..];
+        let entry = match name.find('/') {
+            Some(separator) => Entry {
+                name: name[..separator].to_string(),
+                size: None,
+                modified: None,
+                is_dir: true,
+            },
+            None => Entry {
+                name: name.to_string(),
+                size: Some(data.len() as u64),
+                modified: None,
+                is_dir: false,
+            },
+        };
+
+        if !entries.iter().any(|existing| existing.name == entry.name) {
+            entries.push(entry);
+        }
+    }
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+}
+
+#[derive(Debug)]
+pub struct MemoryReader {
+    pub filename: String,
+    pub position: u64,
+    pub data: Bytes,
+    pub buffer: Buffer,
+    pub stats: TransferStats,
+}
+
+impl MemoryReader {
+    pub fn from_data<D: Into<Bytes>>(data: D) -> MemoryReader {
+        let mut reader = MemoryReader::new();
+        reader.data = data.into();
+        reader
+    }
+
+    fn end_position(&self) -> u64 {
+        let size = self.data.len() as u64;
+        match self.buffer.max_end_position {
+            Some(max) => cmp::min(size, max.saturating_add(1)),
+            None => size,
+        }
+    }
+
+    /// End of the data served by one `read` or `fill_buf` call, limited to
+    /// the cache size like the other readers.
+    fn chunk_end(&self) -> u64 {
+        let end_position = self.end_position();
+        match self.buffer.size {
+            Some(size) => cmp::min(end_position, self.position.saturating_add(size as u64)),
+            None => end_position,
+        }
+    }
+}
+
+impl Reader for MemoryReader {
+    fn new() -> MemoryReader {
+        MemoryReader {
+            filename: "".to_string(),
+            position: 0,
+            data: Bytes::new(),
+            buffer: Buffer::create(),
+            stats: TransferStats::default(),
+        }
+    }
+
+    fn open(&mut self, filename: &str) -> Result<(), String> {
+        self.filename = filename.to_string();
+
+        let registry = REGISTRY.lock().unwrap();
+        match registry.get(filename) {
+            Some(data) => {
+                self.data = data.clone();
+                self.position = 0;
+                Ok(())
+            }
+            None => Err(format!("No memory data registered for {}", filename)),
+        }
+    }
+
+    fn get_position(&mut self) -> Result<u64, String> {
+        Ok(self.position)
+    }
+
+    fn get_cache_size(&self) -> Option<usize> {
+        self.buffer.size
+    }
+
+    fn set_cache_size(&mut self, cache_size: Option<usize>) {
+        self.buffer.size = cache_size;
+    }
+
+    fn get_max_end_position(&self) -> Option<u64> {
+        self.buffer.max_end_position
+    }
+
+    fn set_max_end_position(&mut self, max_end_position: Option<u64>) {
+        self.buffer.max_end_position = max_end_position;
+    }
+
+    fn get_size(&mut self) -> Result<u64, String> {
+        Ok(self.data.len() as u64)
+    }
+}
+
+impl Read for MemoryReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let end_position = self.chunk_end();
+        if self.position >= end_position {
+            return Ok(0);
+        }
+
+        let start = self.position as usize;
+        let size = cmp::min(buf.len(), (end_position - self.position) as usize);
+        buf[..size].clone_from_slice(&self.data[start..start + size]);
+        self.position += size as u64;
+        self.stats.bytes_delivered += size as u64;
+        Ok(size)
+    }
+}
+
+impl BufRead for MemoryReader {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        let end_position = self.chunk_end();
+        let start = cmp::min(self.position, end_position) as usize;
+        Ok(&self.data[start..end_position as usize])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        let end_position = self.end_position();
+        let position = cmp::min(
+            self.position + amount as u64,
+            cmp::max(self.position, end_position),
+        );
+        self.stats.bytes_delivered += position - self.position;
+        self.position = position;
+    }
+}
+
+impl ReadAt for MemoryReader {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        if offset >= self.data.len() as u64 {
+            return Ok(0);
+        }
+
+        let start = offset as usize;
+        let size = cmp::min(buf.len(), self.data.len() - start);
+        buf[..size].clone_from_slice(&self.data[start..start + size]);
+        Ok(size)
+    }
+}
+
+impl Seek for MemoryReader {
+    fn seek(&mut self, seek_from: SeekFrom) -> Result<u64, Error> {
+        let position = match seek_from {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+            SeekFrom::End(offset) => self.data.len() as i64 + offset,
+        };
+
+        if position < 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        }
+
+        self.position = position as u64;
+        Ok(self.position)
+    }
+}
@@ -0,0 +1,10 @@
+use std::time::SystemTime;
+
+/// Properties of a location, as returned by `file_api::stat`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metadata {
+    pub size: Option<u64>,
+    pub modified: Option<SystemTime>,
+    pub content_type: Option<String>,
+    pub is_dir: bool,
+}
@@ -0,0 +1,100 @@
+use hyperx::header::ContentRangeSpec;
+
+use std::str::FromStr;
+
+/// A part of a `multipart/byteranges` body, with the offset of its first byte.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BytePart {
+    pub start: u64,
+    pub data: Vec<u8>,
+}
+
+/// Return the boundary of a `multipart/byteranges` content type.
+pub fn byteranges_boundary(content_type: &str) -> Option<String> {
+    let mut parameters = content_type.split(';');
+    if !parameters
+        .next()?
+        .trim()
+        .eq_ignore_ascii_case("multipart/byteranges")
+    {
+        return None;
+    }
+
+    for parameter in parameters {
+        let mut key_value = parameter.splitn(2, '=');
+        let key = key_value.next()?.trim();
+        if key.eq_ignore_ascii_case("boundary") {
+            let value = key_value.next()?.trim().trim_matches('"');
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+fn find(data: &[u8], pattern: &[u8], from: usize) -> Option<usize> {
+    if from > data.len() {
+        return None;
+    }
+    data[from..]
+        .windows(pattern.len())
+        .position(|window| window == pattern)
+        .map(|position| from + position)
+}
+
+fn parse_part_range(headers: &str) -> Result<(u64, u64), String> {
+    for line in headers.split("\r\n") {
+        let mut name_value = line.splitn(2, ':');
+        let name = name_value.next().unwrap_or("").trim();
+        if !name.eq_ignore_ascii_case("content-range") {
+            continue;
+        }
+
+        let value = name_value.next().unwrap_or("").trim();
+        return match ContentRangeSpec::from_str(value)
+            .map_err(|msg| format!("Invalid part content range {}: {}", value, msg))?
+        {
+            ContentRangeSpec::Bytes {
+                range: Some((start, end)),
+                ..
+            } if start <= end => Ok((start, end)),
+            _ => Err(format!("Unsupported part content range {}", value)),
+        };
+    }
+    Err("Missing part content range".to_string())
+}
+
+/// Split a `multipart/byteranges` body into its parts, checking that each
+/// part holds as many bytes as its `Content-Range` announces.
+pub fn parse_byteranges(body: &[u8], boundary: &str) -> Result<Vec<BytePart>, String> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let missing_delimiter = || "Missing multipart delimiter".to_string();
+
+    let mut parts = vec![];
+    let mut position = find(body, &delimiter, 0).ok_or_else(missing_delimiter)? + delimiter.len();
+
+    while !body[position..].starts_with(b"--") {
+        let line_end = find(body, b"\r\n", position).ok_or_else(missing_delimiter)?;
+        let headers_end = find(body, b"\r\n\r\n", line_end)
+            .ok_or_else(|| "Missing multipart part headers".to_string())?;
+        let headers = String::from_utf8_lossy(&body[line_end + 2..headers_end]);
+        let (start, end) = parse_part_range(&headers)?;
+
+        let data_start = headers_end + 4;
+        let mut next_delimiter = b"\r\n".to_vec();
+        next_delimiter.extend_from_slice(&delimiter);
+        let data_end = find(body, &next_delimiter, data_start).ok_or_else(missing_delimiter)?;
+
+        let data = &body[data_start..data_end];
+        if data.len() as u64 != end - start + 1 {
+            return Err(format!("Part {}-{} holds {} bytes", start, end, data.len()));
+        }
+
+        parts.push(BytePart {
+            start,
+            data: data.to_vec(),
+        });
+        position = data_end + next_delimiter.len();
+    }
+
+    Ok(parts)
+}
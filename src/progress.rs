@@ -0,0 +1,82 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// State of a read, passed to `ProgressObserver::on_progress`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress {
+    pub position: u64,
+    /// Total size as returned by `get_size`, when known.
+    pub size: Option<u64>,
+    /// Throughput since the previous report.
+    pub bytes_per_second: f64,
+}
+
+pub trait ProgressObserver: fmt::Debug + Send + Sync {
+    fn on_progress(&self, progress: &Progress);
+}
+
+/// Call an observer at most once per `interval` while data is read.
+#[derive(Debug)]
+pub struct ProgressReporter {
+    pub observer: Arc<dyn ProgressObserver>,
+    pub interval: Duration,
+    last_report: Instant,
+    readed_size: u64,
+    /// Total size, looked up once before the first report.
+    size: Option<Option<u64>>,
+}
+
+impl ProgressReporter {
+    pub fn new(observer: Arc<dyn ProgressObserver>, interval: Duration) -> ProgressReporter {
+        ProgressReporter {
+            observer,
+            interval,
+            last_report: Instant::now(),
+            readed_size: 0,
+            size: None,
+        }
+    }
+
+    /// Whether the total size has to be looked up before the next report.
+    pub fn needs_size(&self) -> bool {
+        self.size.is_none()
+    }
+
+    pub fn set_size(&mut self, size: Option<u64>) {
+        self.size = Some(size);
+    }
+
+    /// Look up the size again, for a reader opened on another file.
+    pub fn reset_size(&mut self) {
+        self.size = None;
+    }
+
+    /// Account for `size` bytes read and tell whether a report is due,
+    /// either because the interval elapsed or because the end was reached.
+    pub fn advance(&mut self, size: usize) -> bool {
+        self.readed_size += size as u64;
+        if size == 0 {
+            return self.readed_size > 0;
+        }
+        self.last_report.elapsed() >= self.interval
+    }
+
+    pub fn report(&mut self, position: u64) {
+        let elapsed = self.last_report.elapsed();
+        let elapsed_seconds = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9;
+        let bytes_per_second = if elapsed_seconds > 0.0 {
+            self.readed_size as f64 / elapsed_seconds
+        } else {
+            0.0
+        };
+
+        self.observer.on_progress(&Progress {
+            position,
+            size: self.size.unwrap_or(None),
+            bytes_per_second,
+        });
+        self.last_report = Instant::now();
+        self.readed_size = 0;
+    }
+}
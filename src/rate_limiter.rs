@@ -0,0 +1,102 @@
+use cancellation::CancellationToken;
+use error::{Error, TimeoutKind};
+use timeouts::remaining_time;
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Token bucket limiting the bandwidth of remote reads.
+///
+/// Share it between readers with an `Arc` to enforce a process-wide budget.
+#[derive(Debug)]
+pub struct RateLimiter {
+    pub bytes_per_second: u64,
+    pub burst: u64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Allow `bytes_per_second` on average and up to `burst` bytes at once,
+    /// a zero rate disables the limit.
+    pub fn new(bytes_per_second: u64, burst: u64) -> RateLimiter {
+        RateLimiter {
+            bytes_per_second,
+            burst,
+            state: Mutex::new(BucketState {
+                tokens: burst as f64,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take `size` tokens and return how long the caller must wait for them.
+    ///
+    /// The bucket may go into debt, so later callers wait for earlier reservations.
+    pub fn reserve(&self, size: u64) -> Duration {
+        let mut state = self.state.lock().unwrap();
+
+        let now = Instant::now();
+        let elapsed = now - state.updated;
+        let elapsed_seconds = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9;
+        state.tokens =
+            (state.tokens + elapsed_seconds * self.bytes_per_second as f64).min(self.burst as f64);
+        state.updated = now;
+
+        state.tokens -= size as f64;
+        if state.tokens >= 0.0 || self.bytes_per_second == 0 {
+            return Duration::from_secs(0);
+        }
+
+        let wait = -state.tokens / self.bytes_per_second as f64;
+        Duration::new(wait.trunc() as u64, (wait.fract() * 1e9) as u32)
+    }
+
+    /// Block until `size` bytes may be transferred.
+    pub fn acquire(&self, size: u64) {
+        let wait = self.reserve(size);
+        if wait > Duration::from_secs(0) {
+            thread::sleep(wait);
+        }
+    }
+
+    /// Like `acquire`, but stop waiting when `cancellation` is cancelled or
+    /// `deadline` is reached. Return how long the caller was throttled.
+    pub fn acquire_with(
+        &self,
+        size: u64,
+        cancellation: &CancellationToken,
+        deadline: Option<Instant>,
+    ) -> Result<Duration, Error> {
+        let wait = self.reserve(size);
+        let remaining = match deadline {
+            Some(deadline) if wait > Duration::from_secs(0) => Some(remaining_time(deadline)?),
+            _ => None,
+        };
+        match remaining {
+            Some(remaining) if remaining < wait => {
+                cancellation.sleep(remaining)?;
+                Err(Error::Timeout(TimeoutKind::Deadline))
+            }
+            _ => {
+                cancellation.sleep(wait)?;
+                Ok(wait)
+            }
+        }
+    }
+
+    /// Largest read worth doing at once without exceeding the burst size.
+    pub fn chunk_size(&self, size: usize) -> usize {
+        if self.burst == 0 || self.burst >= size as u64 {
+            size
+        } else {
+            self.burst as usize
+        }
+    }
+}
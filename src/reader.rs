@@ -1,3 +1,5 @@
+use std::io::Error;
+
 pub trait Reader {
     fn new() -> Self;
     fn open(&mut self, filename: &str) -> Result<(), String>;
@@ -11,3 +13,10 @@ pub trait Reader {
     fn get_position(&mut self) -> Result<u64, String>;
     fn get_size(&mut self) -> Result<u64, String>;
 }
+
+/// Positional reads that leave the reader cursor untouched, so a reader can
+/// be shared between threads. Reads are bounded by the source size only,
+/// `max_end_position` does not apply.
+pub trait ReadAt {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error>;
+}
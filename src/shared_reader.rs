@@ -0,0 +1,73 @@
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+use reader::{ReadAt, Reader};
+
+/// Cloneable handle reading one opened source from several threads.
+///
+/// Every clone keeps its own position and reads through `ReadAt`, so the
+/// source is opened once and never seeked.
+#[derive(Debug)]
+pub struct SharedReader<R> {
+    pub reader: Arc<R>,
+    pub size: Option<u64>,
+    pub position: u64,
+}
+
+impl<R> Clone for SharedReader<R> {
+    fn clone(&self) -> SharedReader<R> {
+        SharedReader {
+            reader: self.reader.clone(),
+            size: self.size,
+            position: self.position,
+        }
+    }
+}
+
+impl<R: Reader + ReadAt + Send + Sync> SharedReader<R> {
+    /// Share an opened `reader`, its size is queried once here.
+    pub fn create(mut reader: R) -> SharedReader<R> {
+        let size = reader.get_size().ok();
+        SharedReader {
+            reader: Arc::new(reader),
+            size,
+            position: 0,
+        }
+    }
+}
+
+impl<R: ReadAt> ReadAt for SharedReader<R> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        self.reader.read_at(offset, buf)
+    }
+}
+
+impl<R: ReadAt> Read for SharedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let readed_size = self.reader.read_at(self.position, buf)?;
+        self.position += readed_size as u64;
+        Ok(readed_size)
+    }
+}
+
+impl<R: ReadAt> Seek for SharedReader<R> {
+    fn seek(&mut self, seek_from: SeekFrom) -> Result<u64, Error> {
+        let position = match seek_from {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+            SeekFrom::End(offset) => match self.size {
+                Some(size) => size as i64 + offset,
+                None => return Err(Error::new(ErrorKind::Other, "Missing file size")),
+            },
+        };
+        if position < 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        }
+
+        self.position = position as u64;
+        Ok(self.position)
+    }
+}
@@ -0,0 +1,44 @@
+use percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
+use reqwest::header::{HeaderMap, HeaderValue};
+
+use azure::AzureCredentials;
+use error::Error;
+use gcs::GcsCredentials;
+
+/// Post an url-encoded form to a token endpoint and return the response body,
+/// with the client, the timeouts and the cancellation of the signed request.
+pub type TokenExchange<'a> = dyn Fn(&str, &[(&str, &str)]) -> Result<String, Error> + 'a;
+
+/// Authentication applied to every request issued by an `HttpReader`.
+#[derive(Clone, Debug)]
+pub enum Signer {
+    Azure(AzureCredentials),
+    Gcs(GcsCredentials),
+}
+
+impl Signer {
+    pub fn sign(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &mut HeaderMap,
+        exchange: &TokenExchange,
+    ) -> Result<(), Error> {
+        match *self {
+            Signer::Azure(ref credentials) => Ok(credentials.sign(method, url, headers)?),
+            Signer::Gcs(ref credentials) => credentials.sign(method, url, headers, exchange),
+        }
+    }
+}
+
+pub fn to_header_value(value: &str) -> Result<HeaderValue, String> {
+    HeaderValue::from_str(value).map_err(|msg| msg.to_string())
+}
+
+/// Percent-encode each segment of an object path, keeping the `/` separators.
+pub fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT_ENCODE_SET).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
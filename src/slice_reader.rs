@@ -0,0 +1,192 @@
+use std::cmp;
+use std::io::{BufRead, Error, ErrorKind, Read, Seek, SeekFrom};
+
+use reader::{ReadAt, Reader};
+
+/// Expose the byte window `[start, end)` of another reader as a standalone stream.
+/// Without `end`, the window extends to the end of the underlying reader.
+#[derive(Debug)]
+pub struct SliceReader<R> {
+    pub reader: R,
+    pub start: u64,
+    pub end: Option<u64>,
+    pub position: u64,
+    pub max_end_position: Option<u64>,
+    length: u64,
+    synchronized: bool,
+}
+
+impl<R: Reader + Read + Seek> SliceReader<R> {
+    pub fn create(reader: R, start: u64, end: u64) -> SliceReader<R> {
+        let mut slice = SliceReader {
+            reader,
+            start,
+            end: Some(cmp::max(start, end)),
+            position: 0,
+            max_end_position: None,
+            length: end.saturating_sub(start),
+            synchronized: false,
+        };
+        slice.update_max_end_position();
+        slice
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn len(&self) -> u64 {
+        self.length
+    }
+
+    /// Clamp the window to the size of the underlying reader.
+    fn resolve_length(&mut self) -> Result<u64, String> {
+        let size = self.reader.get_size()?;
+        let end = match self.end {
+            Some(end) => cmp::min(end, size),
+            None => size,
+        };
+        self.length = end.saturating_sub(self.start);
+        self.update_max_end_position();
+        Ok(self.length)
+    }
+
+    fn end_position(&self) -> u64 {
+        match self.max_end_position {
+            Some(max) => cmp::min(self.len(), max.saturating_add(1)),
+            None => self.len(),
+        }
+    }
+
+    fn update_max_end_position(&mut self) {
+        let end_position = self.end_position();
+        if end_position > 0 {
+            self.reader
+                .set_max_end_position(Some(self.start + end_position - 1));
+        }
+    }
+}
+
+impl<R: Reader + Read + Seek> Reader for SliceReader<R> {
+    fn new() -> SliceReader<R> {
+        SliceReader {
+            reader: R::new(),
+            start: 0,
+            end: None,
+            position: 0,
+            max_end_position: None,
+            length: 0,
+            synchronized: false,
+        }
+    }
+
+    fn open(&mut self, filename: &str) -> Result<(), String> {
+        self.reader.open(filename)?;
+        self.position = 0;
+        self.synchronized = false;
+        self.resolve_length()?;
+        Ok(())
+    }
+
+    fn get_position(&mut self) -> Result<u64, String> {
+        Ok(self.position)
+    }
+
+    fn get_cache_size(&self) -> Option<usize> {
+        self.reader.get_cache_size()
+    }
+
+    fn set_cache_size(&mut self, cache_size: Option<usize>) {
+        self.reader.set_cache_size(cache_size)
+    }
+
+    fn get_max_end_position(&self) -> Option<u64> {
+        self.max_end_position
+    }
+
+    fn set_max_end_position(&mut self, max_end_position: Option<u64>) {
+        self.max_end_position = max_end_position;
+        self.update_max_end_position();
+    }
+
+    fn get_size(&mut self) -> Result<u64, String> {
+        self.resolve_length()
+    }
+}
+
+impl<R: Reader + Read + Seek> Read for SliceReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let end_position = self.end_position();
+        if self.position >= end_position {
+            return Ok(0);
+        }
+
+        if !self.synchronized {
+            self.reader
+                .seek(SeekFrom::Start(self.start + self.position))?;
+            self.synchronized = true;
+        }
+
+        let size = cmp::min(buf.len() as u64, end_position - self.position) as usize;
+        let readed_size = self.reader.read(&mut buf[..size])?;
+        self.position += readed_size as u64;
+        Ok(readed_size)
+    }
+}
+
+impl<R: Reader + BufRead + Seek> BufRead for SliceReader<R> {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        let end_position = self.end_position();
+        if self.position >= end_position {
+            return Ok(&[]);
+        }
+
+        if !self.synchronized {
+            self.reader
+                .seek(SeekFrom::Start(self.start + self.position))?;
+            self.synchronized = true;
+        }
+
+        let size = (end_position - self.position) as usize;
+        let data = self.reader.fill_buf()?;
+        Ok(&data[..cmp::min(size, data.len())])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount);
+        self.position += amount as u64;
+    }
+}
+
+impl<R: Reader + Read + Seek + ReadAt> ReadAt for SliceReader<R> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        if offset >= self.len() {
+            return Ok(0);
+        }
+
+        let size = cmp::min(buf.len() as u64, self.len() - offset) as usize;
+        self.reader.read_at(self.start + offset, &mut buf[..size])
+    }
+}
+
+impl<R: Reader + Read + Seek> Seek for SliceReader<R> {
+    fn seek(&mut self, seek_from: SeekFrom) -> Result<u64, Error> {
+        let position = match seek_from {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+            SeekFrom::End(offset) => self.len() as i64 + offset,
+        };
+        if position < 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        }
+
+        if position as u64 != self.position {
+            self.position = position as u64;
+            self.synchronized = false;
+        }
+        Ok(self.position)
+    }
+}
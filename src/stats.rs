@@ -0,0 +1,65 @@
+use error::Error;
+
+use std::fmt;
+use std::time::Duration;
+
+/// Counters accumulated by a reader since it was created.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransferStats {
+    /// Requests sent to the origin (or reads issued to the file system).
+    pub requests: u64,
+    /// Requests that failed.
+    pub failures: u64,
+    /// Requests repeated after a failure. Always 0 for now, as failed requests
+    /// are reported to the caller without being retried.
+    pub retries: u64,
+    /// Bytes received from the origin.
+    pub bytes_fetched: u64,
+    /// Bytes returned to the caller by `read`.
+    pub bytes_delivered: u64,
+    /// Reads served from the cache without a request.
+    pub cache_hits: u64,
+    /// Reads that needed a request to fill the cache.
+    pub cache_misses: u64,
+    /// Time spent waiting on requests.
+    pub latency: Duration,
+}
+
+/// Description of one completed request, passed to `Metrics::on_request`.
+#[derive(Debug)]
+pub struct RequestEvent<'a> {
+    pub url: &'a str,
+    pub method: &'a str,
+    pub position: u64,
+    pub size: u64,
+    pub latency: Duration,
+    pub error: Option<&'a Error>,
+}
+
+/// Hook called for every request, e.g. to export counters to Prometheus.
+pub trait Metrics: fmt::Debug + Send + Sync {
+    fn on_request(&self, event: &RequestEvent);
+}
+
+impl TransferStats {
+    pub fn record(&mut self, event: &RequestEvent) {
+        self.requests += 1;
+        if event.error.is_some() {
+            self.failures += 1;
+        }
+        self.bytes_fetched += event.size;
+        self.latency += event.latency;
+    }
+
+    /// Add the counters of `other`, e.g. of another segment.
+    pub fn merge(&mut self, other: &TransferStats) {
+        self.requests += other.requests;
+        self.failures += other.failures;
+        self.retries += other.retries;
+        self.bytes_fetched += other.bytes_fetched;
+        self.bytes_delivered += other.bytes_delivered;
+        self.cache_hits += other.cache_hits;
+        self.cache_misses += other.cache_misses;
+        self.latency += other.latency;
+    }
+}
@@ -0,0 +1,81 @@
+use reqwest::ClientBuilder;
+
+use error::{Error, TimeoutKind};
+
+use std::cmp;
+use std::time::{Duration, Instant};
+
+/// Time limits applied to remote requests.
+///
+/// All limits are disabled by default, which keeps reqwest's own defaults.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Maximum time to establish a connection.
+    pub connect: Option<Duration>,
+    /// Maximum time for a single request, waiting for the response and reading its body.
+    pub read: Option<Duration>,
+    /// Maximum time for the whole operation, from `open` to the last read.
+    pub deadline: Option<Duration>,
+    /// Abort a transfer whose throughput stays below this rate after `stall_window`.
+    pub min_bytes_per_second: Option<u64>,
+    /// Time allowed before the throughput is checked, one second when unset.
+    pub stall_window: Option<Duration>,
+}
+
+impl Timeouts {
+    /// Instant after which the operation started at `start` must fail.
+    pub fn deadline_from(&self, start: Instant) -> Option<Instant> {
+        self.deadline.map(|deadline| start + deadline)
+    }
+
+    /// Apply the connect and read timeouts to `builder`, clamping the read
+    /// timeout to the time left before `deadline`.
+    pub fn configure(
+        &self,
+        builder: ClientBuilder,
+        deadline: Option<Instant>,
+    ) -> Result<ClientBuilder, Error> {
+        let builder = match self.connect {
+            Some(connect) => builder.connect_timeout(connect),
+            None => builder,
+        };
+
+        let remaining = match deadline {
+            Some(deadline) => Some(remaining_time(deadline)?),
+            None => None,
+        };
+        let timeout = match (self.read, remaining) {
+            (Some(read), Some(remaining)) => Some(cmp::min(read, remaining)),
+            (read, remaining) => read.or(remaining),
+        };
+
+        Ok(match timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        })
+    }
+
+    /// Fail when `size` bytes transferred since `start` are under the minimum rate.
+    pub fn check_progress(&self, start: Instant, size: u64) -> Result<(), Error> {
+        if let Some(min_bytes_per_second) = self.min_bytes_per_second {
+            let window = self.stall_window.unwrap_or_else(|| Duration::from_secs(1));
+            let elapsed = start.elapsed();
+            if elapsed >= window {
+                let elapsed_millis = elapsed.as_secs() * 1_000 + u64::from(elapsed.subsec_millis());
+                if size * 1_000 < min_bytes_per_second * elapsed_millis {
+                    return Err(Error::Timeout(TimeoutKind::Stall));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Time left before `deadline`, or a deadline timeout once it has passed.
+pub fn remaining_time(deadline: Instant) -> Result<Duration, Error> {
+    let now = Instant::now();
+    if now >= deadline {
+        return Err(Error::Timeout(TimeoutKind::Deadline));
+    }
+    Ok(deadline - now)
+}
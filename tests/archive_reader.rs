@@ -0,0 +1,142 @@
+extern crate file_api;
+
+use file_api::archive_reader::{self, ArchiveKind};
+use file_api::memory_reader;
+use file_api::reader::Reader;
+use file_api::MainReader;
+
+use std::io::{Read, Seek, SeekFrom};
+
+#[test]
+fn archive_split_member() {
+    let (archive, kind, member) =
+        archive_reader::split_member("http://source/archive.ZIP#path/inside.mxf").unwrap();
+    assert_eq!(archive, "http://source/archive.ZIP");
+    assert_eq!(kind, ArchiveKind::Zip);
+    assert_eq!(member, "path/inside.mxf");
+
+    assert!(archive_reader::split_member("archive.tar#").is_none());
+    assert!(archive_reader::split_member("archive.mxf#inside.mxf").is_none());
+}
+
+#[test]
+fn archive_exists() {
+    assert!(file_api::exists(
+        "tests/sample_archive.zip#folder/other_file.txt"
+    ));
+    assert!(!file_api::exists(
+        "tests/sample_archive.zip#bad_filename.txt"
+    ));
+    assert!(file_api::exists(
+        "tests/sample_archive.tar#sample_data_file.txt"
+    ));
+    assert!(!file_api::exists(
+        "tests/sample_archive.tar#bad_filename.txt"
+    ));
+}
+
+#[test]
+fn zip_read_stored_member() {
+    let mut reader = MainReader::new();
+    reader
+        .open("tests/sample_archive.zip#sample_data_file.txt")
+        .unwrap();
+    assert_eq!(reader.get_size().unwrap(), 20);
+
+    let mut data = String::new();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "somedataandsomemore\n");
+
+    let position = reader.seek(SeekFrom::Start(4)).unwrap();
+    assert_eq!(position, 4);
+
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"data");
+}
+
+#[test]
+fn zip_compressed_member() {
+    let mut reader = MainReader::new();
+    let result = reader.open("tests/sample_archive.zip#compressed.txt");
+    assert!(result.unwrap_err().contains("only stored members"));
+}
+
+#[test]
+fn tar_read_member() {
+    let mut reader = MainReader::new();
+    reader
+        .open("tests/sample_archive.tar#folder/other_file.txt")
+        .unwrap();
+    assert_eq!(reader.get_size().unwrap(), 14);
+
+    let mut data = String::new();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "other content\n");
+}
+
+#[test]
+fn tar_read_long_name_member() {
+    let filename = format!("tests/sample_archive.tar#folder/{}.txt", "a".repeat(120));
+    let mut reader = MainReader::new();
+    reader.open(&filename).unwrap();
+
+    let mut data = String::new();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "long name\n");
+}
+
+#[test]
+fn archive_split_presigned_url() {
+    let (archive, kind, member) = archive_reader::split_member(
+        "https://bucket.s3.amazonaws.com/archive.tar?X-Amz-Signature=abc#inside.mxf",
+    )
+    .unwrap();
+    assert_eq!(
+        archive,
+        "https://bucket.s3.amazonaws.com/archive.tar?X-Amz-Signature=abc"
+    );
+    assert_eq!(kind, ArchiveKind::Tar);
+    assert_eq!(member, "inside.mxf");
+}
+
+fn tar_header(name: &str, size: &str, type_flag: u8) -> Vec<u8> {
+    let mut header = vec![0; 512];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[124..124 + size.len()].copy_from_slice(size.as_bytes());
+    header[156] = type_flag;
+    header
+}
+
+#[test]
+fn tar_dot_slash_member() {
+    let mut archive = tar_header("./inside.txt", "00000000004", b'0');
+    archive.extend_from_slice(b"data");
+    archive.resize(2048, 0);
+    memory_reader::register("mem://dot_slash.tar", archive);
+
+    let mut reader = MainReader::new();
+    reader.open("mem://dot_slash.tar#inside.txt").unwrap();
+    let mut data = String::new();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "data");
+}
+
+#[test]
+fn archive_hostile_metadata_size() {
+    let mut archive = tar_header("././@LongLink", "77777777777", b'L');
+    archive.resize(2048, 0);
+    memory_reader::register("mem://hostile.tar", archive);
+    let error = MainReader::new()
+        .open("mem://hostile.tar#inside.txt")
+        .unwrap_err();
+    assert!(error.contains("Invalid archive metadata"), "{}", error);
+
+    let mut archive = vec![0x50, 0x4b, 0x05, 0x06, 0, 0, 0, 0, 1, 0, 1, 0];
+    archive.extend_from_slice(&[0xf0, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0]);
+    memory_reader::register("mem://hostile.zip", archive);
+    let error = MainReader::new()
+        .open("mem://hostile.zip#inside.txt")
+        .unwrap_err();
+    assert!(error.contains("Invalid archive metadata"), "{}", error);
+}
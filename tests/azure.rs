@@ -0,0 +1,75 @@
+extern crate base64;
+extern crate file_api;
+extern crate reqwest;
+
+use file_api::azure::{AzureAccess, AzureCredentials};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, RANGE};
+
+fn azurite_credentials(access: AzureAccess) -> AzureCredentials {
+    AzureCredentials {
+        account: "devstoreaccount1".to_string(),
+        endpoint: "http://127.0.0.1:10000/devstoreaccount1".to_string(),
+        access,
+    }
+}
+
+#[test]
+fn azure_blob_url() {
+    let credentials = azurite_credentials(AzureAccess::Anonymous);
+    let url = credentials
+        .blob_url("az://container/path/blob.txt")
+        .unwrap();
+    assert_eq!(
+        url,
+        "http://127.0.0.1:10000/devstoreaccount1/container/path/blob.txt"
+    );
+
+    assert!(credentials.blob_url("az://container").is_err());
+}
+
+#[test]
+fn azure_blob_url_encoding() {
+    let credentials = azurite_credentials(AzureAccess::Anonymous);
+    let url = credentials
+        .blob_url("az://container/my dir/blob #1?.txt")
+        .unwrap();
+    assert_eq!(
+        url,
+        "http://127.0.0.1:10000/devstoreaccount1/container/my%20dir/blob%20%231%3F.txt"
+    );
+}
+
+#[test]
+fn azure_blob_url_with_sas() {
+    let credentials = azurite_credentials(AzureAccess::Sas("sv=2019-02-02&sig=abc".to_string()));
+    let url = credentials.blob_url("az://container/blob.txt").unwrap();
+    assert_eq!(
+        url,
+        "http://127.0.0.1:10000/devstoreaccount1/container/blob.txt?sv=2019-02-02&sig=abc"
+    );
+}
+
+#[test]
+fn azure_shared_key_signature() {
+    let key = base64::decode(
+        "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==",
+    )
+    .unwrap();
+    let credentials = azurite_credentials(AzureAccess::SharedKey(key));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(RANGE, HeaderValue::from_static("bytes=0-3"));
+    headers.insert(
+        "x-ms-date",
+        HeaderValue::from_static("Tue, 01 Jan 2019 00:00:00 GMT"),
+    );
+
+    let url = credentials.blob_url("az://container/blob.txt").unwrap();
+    credentials.sign("GET", &url, &mut headers).unwrap();
+
+    assert_eq!(
+        headers.get(AUTHORIZATION).unwrap(),
+        "SharedKey devstoreaccount1:7OnHdAxKT34ulB6anwNc47yGdhWXmE5dRwjByDZHQ2w="
+    );
+    assert_eq!(headers.get("x-ms-version").unwrap(), "2019-02-02");
+}
@@ -0,0 +1,71 @@
+extern crate file_api;
+
+use file_api::memory_reader;
+use file_api::reader::Reader;
+use file_api::MainReader;
+
+use std::io::{BufRead, Read, Seek, SeekFrom};
+
+fn check_lines(filename: &str, expected: &[&str]) {
+    let mut reader = MainReader::new();
+    reader.open(filename).unwrap();
+
+    let mut position = 0;
+    for expected_line in expected {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(&line, expected_line, "reading {}", filename);
+
+        position += line.len() as u64;
+        assert_eq!(reader.get_position().unwrap(), position);
+    }
+    assert!(reader.fill_buf().unwrap().is_empty());
+}
+
+#[test]
+fn buf_read_all_readers() {
+    memory_reader::register("mem://buf_read/lines.txt", b"first\nsecond\n".to_vec());
+    memory_reader::register("mem://buf_read/part1", b"fir".to_vec());
+    memory_reader::register("mem://buf_read/part2", b"st\nsecond\n".to_vec());
+
+    check_lines("tests/sample_data_file.txt", &["somedataandsomemore\n"]);
+    check_lines(
+        "tests/sample_archive.zip#sample_data_file.txt",
+        &["somedataandsomemore\n"],
+    );
+    check_lines(
+        "tests/sample_archive.tar#sample_data_file.txt",
+        &["somedataandsomemore\n"],
+    );
+    check_lines("mem://buf_read/lines.txt", &["first\n", "second\n"]);
+    check_lines("data:,first%0Asecond%0A", &["first\n", "second\n"]);
+    check_lines(
+        "concat:mem://buf_read/part1|mem://buf_read/part2",
+        &["first\n", "second\n"],
+    );
+}
+
+#[test]
+fn buf_read_then_read_and_seek() {
+    for use_mmap in &[false, true] {
+        let mut reader = MainReader::new();
+        reader.set_mmap(*use_mmap);
+        reader.open("tests/sample_data_file.txt").unwrap();
+
+        assert_eq!(&reader.fill_buf().unwrap()[..4], b"some");
+        reader.consume(4);
+        assert_eq!(reader.get_position().unwrap(), 4);
+
+        let mut data = [0; 4];
+        reader.read_exact(&mut data).unwrap();
+        assert_eq!(&data, b"data");
+
+        reader.seek(SeekFrom::Current(3)).unwrap();
+        reader.read_exact(&mut data).unwrap();
+        assert_eq!(&data, b"some");
+        assert_eq!(reader.get_position().unwrap(), 15);
+
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(&reader.fill_buf().unwrap()[..4], b"some");
+    }
+}
@@ -0,0 +1,121 @@
+extern crate file_api;
+
+mod common;
+
+use common::serve;
+use file_api::buffer::Buffer;
+use file_api::reader::Reader;
+use file_api::MainReader;
+
+use std::io::{BufRead, Read, Seek, SeekFrom};
+
+#[test]
+fn buffer_cursor() {
+    let mut buffer = Buffer::create();
+    buffer.append_data(b"0123456789");
+
+    let mut data = [0; 4];
+    assert!(buffer.get_data(&mut data));
+    assert_eq!(&data, b"0123");
+    assert_eq!(buffer.get_cached_size(), 6);
+    assert_eq!(buffer.get_cached_data(), b"456789");
+
+    buffer.consume(2);
+    assert_eq!(buffer.get_cached_data(), b"6789");
+
+    buffer.append_data(b"ab");
+    assert_eq!(buffer.offset(), 0);
+    assert_eq!(buffer.get_cached_data(), b"6789ab");
+
+    let mut data = [0; 7];
+    assert!(!buffer.get_data(&mut data));
+
+    buffer.consume(100);
+    assert_eq!(buffer.get_cached_size(), 0);
+    assert_eq!(buffer.offset(), 0);
+    assert!(buffer.get_cached_data().is_empty());
+}
+
+#[test]
+fn http_buf_read() {
+    let content = "first line\nsecond line\n";
+    let mut responses = vec![format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        content.len()
+    )];
+    for start in (0..content.len()).step_by(8) {
+        let end = std::cmp::min(start + 8, content.len());
+        responses.push(format!(
+            "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            start,
+            end - 1,
+            content.len(),
+            end - start,
+            &content[start..end]
+        ));
+    }
+
+    let (url, handler) = serve(responses);
+
+    let mut reader = MainReader::new();
+    reader.open(&url).unwrap();
+    reader.set_cache_size(Some(8));
+
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "first line\n");
+    assert_eq!(reader.get_position().unwrap(), 11);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "second line\n");
+    assert_eq!(reader.get_position().unwrap(), 23);
+
+    assert!(reader.fill_buf().unwrap().is_empty());
+    handler.join().unwrap();
+}
+
+#[test]
+fn http_buf_read_keeps_cache_disabled() {
+    let content = "somedataandsomemore\n";
+    let (url, handler) = serve(vec![
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            content.len()
+        ),
+        format!(
+            "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-19/20\r\n\
+             Content-Length: 20\r\nConnection: close\r\n\r\n{}",
+            content
+        ),
+        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 4-7/20\r\n\
+         Content-Length: 4\r\nConnection: close\r\n\r\ndata"
+            .to_string(),
+    ]);
+
+    let mut reader = MainReader::new();
+    reader.open(&url).unwrap();
+
+    assert_eq!(reader.fill_buf().unwrap(), content.as_bytes());
+    reader.consume(4);
+    assert_eq!(reader.get_cache_size(), None);
+
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"data");
+
+    // Leftover buffered data is delivered before reads go to the server.
+    let mut rest = [0; 100];
+    assert_eq!(reader.read(&mut rest).unwrap(), 12);
+    assert_eq!(&rest[..12], b"andsomemore\n");
+
+    reader.seek(SeekFrom::Start(4)).unwrap();
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"data");
+    assert_eq!(reader.get_cache_size(), None);
+
+    let requests = handler.join().unwrap();
+    assert_eq!(requests.len(), 3);
+    assert!(requests[2].contains("range: bytes=4-7\r\n"));
+}
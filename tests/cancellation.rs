@@ -0,0 +1,235 @@
+extern crate file_api;
+
+mod common;
+
+use common::accept;
+use file_api::cancellation::is_cancelled_error;
+use file_api::follow::Follow;
+use file_api::reader::Reader;
+use file_api::timeouts::Timeouts;
+use file_api::MainReader;
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn cancel_file_reader() {
+    let mut reader = MainReader::new();
+    reader.open("tests/sample_data_file.txt").unwrap();
+    let token = reader.cancellation_token();
+
+    let mut data = [0; 5];
+    reader.read_exact(&mut data).unwrap();
+
+    token.cancel();
+    let error = reader.read(&mut data).unwrap_err();
+    assert!(is_cancelled_error(&error));
+    let error = reader.seek(SeekFrom::Start(0)).unwrap_err();
+    assert!(is_cancelled_error(&error));
+
+    let mut data = vec![];
+    assert!(reader.read_to_end(&mut data).is_err());
+}
+
+#[test]
+fn cancel_in_flight_request() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/file", server.local_addr().unwrap());
+    let handler = thread::spawn(move || {
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000000\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        drop(connection);
+
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(
+                b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-999999/1000000\r\n\
+                  Content-Length: 1000000\r\nConnection: close\r\n\r\n",
+            )
+            .unwrap();
+        for _ in 0..20 {
+            if connection.write_all(&[0; 1000]).is_err() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    });
+
+    let mut reader = MainReader::new();
+    reader.open(&url).unwrap();
+    let token = reader.cancellation_token();
+
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(300));
+        token.cancel();
+    });
+
+    let start = Instant::now();
+    let mut data = vec![0; 1_000_000];
+    let error = reader.read(&mut data).unwrap_err();
+    assert!(is_cancelled_error(&error));
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    canceller.join().unwrap();
+    handler.join().unwrap();
+}
+
+#[test]
+fn cancel_closes_streaming_connection() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/file", server.local_addr().unwrap());
+    let handler = thread::spawn(move || {
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000000\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        drop(connection);
+
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(
+                b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-999999/1000000\r\n\
+                  Content-Length: 1000000\r\nConnection: close\r\n\r\n",
+            )
+            .unwrap();
+        for _ in 0..100 {
+            if connection.write_all(&[0; 1000]).is_err() {
+                return Some(Instant::now());
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        None
+    });
+
+    let mut reader = MainReader::new();
+    reader.open(&url).unwrap();
+    assert_cancelled_quickly(&mut reader);
+    let cancelled = Instant::now();
+
+    let closed = handler.join().unwrap().expect("connection kept open");
+    assert!(closed.duration_since(cancelled) < Duration::from_secs(1));
+}
+
+#[test]
+fn cancel_closes_silent_connection_after_read_timeout() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/file", server.local_addr().unwrap());
+    let handler = thread::spawn(move || {
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        drop(connection);
+
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(
+                b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-99/1000\r\n\
+                  Content-Length: 100\r\nConnection: close\r\n\r\n",
+            )
+            .unwrap();
+        connection
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        // The client closing the connection ends the read.
+        let readed_size = connection.read(&mut [0; 16]).unwrap();
+        assert_eq!(readed_size, 0);
+        Instant::now()
+    });
+
+    let mut reader = MainReader::new();
+    reader.set_timeouts(Timeouts {
+        read: Some(Duration::from_millis(800)),
+        ..Default::default()
+    });
+    reader.open(&url).unwrap();
+    let start = Instant::now();
+    assert_cancelled_quickly(&mut reader);
+
+    let closed = handler.join().unwrap();
+    assert!(closed.duration_since(start) < Duration::from_millis(2000));
+}
+
+/// Serve the `HEAD` request, then accept the `GET` one and send only
+/// `response_head`, keeping the connection silent for a while.
+fn serve_silent(response_head: &'static str) -> (String, thread::JoinHandle<()>) {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/file", server.local_addr().unwrap());
+    let handler = thread::spawn(move || {
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        drop(connection);
+
+        let (mut connection, _) = accept(&server);
+        connection.write_all(response_head.as_bytes()).unwrap();
+        thread::sleep(Duration::from_millis(1500));
+    });
+    (url, handler)
+}
+
+fn assert_cancelled_quickly(reader: &mut MainReader) {
+    let token = reader.cancellation_token();
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        token.cancel();
+    });
+
+    let start = Instant::now();
+    let mut data = [0; 100];
+    let error = reader.read(&mut data).unwrap_err();
+    assert!(is_cancelled_error(&error), "{:?}", error);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    canceller.join().unwrap();
+}
+
+#[test]
+fn cancel_waiting_for_headers() {
+    let (url, handler) = serve_silent("");
+    let mut reader = MainReader::new();
+    reader.open(&url).unwrap();
+    assert_cancelled_quickly(&mut reader);
+    handler.join().unwrap();
+}
+
+#[test]
+fn cancel_silent_body() {
+    let (url, handler) = serve_silent(
+        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-99/1000\r\n\
+         Content-Length: 100\r\nConnection: close\r\n\r\n",
+    );
+    let mut reader = MainReader::new();
+    reader.open(&url).unwrap();
+    assert_cancelled_quickly(&mut reader);
+    handler.join().unwrap();
+}
+
+#[test]
+fn cancel_concat_segment() {
+    let (url, handler) = serve_silent("");
+    let mut reader = MainReader::new();
+    reader
+        .open(&format!("concat:{}|tests/sample_data_file.txt", url))
+        .unwrap();
+    assert_cancelled_quickly(&mut reader);
+    handler.join().unwrap();
+}
+
+#[test]
+fn cancel_follow_wait() {
+    let mut reader = MainReader::new();
+    reader.set_follow(Some(Follow::new(
+        Duration::from_secs(5),
+        Duration::from_secs(10),
+    )));
+    reader.open("tests/sample_data_file.txt").unwrap();
+    let mut data = [0; 20];
+    reader.read_exact(&mut data).unwrap();
+
+    assert_cancelled_quickly(&mut reader);
+}
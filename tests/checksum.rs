@@ -0,0 +1,94 @@
+extern crate file_api;
+
+use file_api::checksum::{Algorithm, Checksum, ChecksumReader};
+use file_api::reader::Reader;
+use file_api::error::Error;
+use file_api::{checksum, checksum_with, verify_checksum, verify_checksum_with, MainReader};
+
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+const SAMPLE_MD5: &str = "61665f570dd27de7e4a5b9272a1aa4b6";
+const SAMPLE_SHA256: &str = "43c8922ec9eec31ce425fafa96abf9e0f24a98cbd0fac39343d91821e261a3a6";
+
+#[test]
+fn checksum_file() {
+    let checksums = checksum(
+        "tests/sample_data_file.txt",
+        &[Algorithm::Md5, Algorithm::Sha256, Algorithm::XxHash64],
+    )
+    .unwrap();
+
+    assert_eq!(checksums[0], Checksum::new(Algorithm::Md5, SAMPLE_MD5));
+    assert_eq!(
+        checksums[1],
+        Checksum::new(Algorithm::Sha256, SAMPLE_SHA256)
+    );
+    assert_eq!(checksums[2].algorithm, Algorithm::XxHash64);
+    assert_eq!(checksums[2].value.len(), 16);
+}
+
+#[test]
+fn verify_file() {
+    let expected = Checksum::new(Algorithm::Sha256, &SAMPLE_SHA256.to_uppercase());
+    verify_checksum("tests/sample_data_file.txt", &expected).unwrap();
+
+    let expected = Checksum::new(Algorithm::Md5, "00000000000000000000000000000000");
+    match verify_checksum("tests/sample_data_file.txt", &expected) {
+        Err(Error::ChecksumMismatch {
+            expected: e,
+            actual,
+        }) => {
+            assert_eq!(e, expected);
+            assert_eq!(actual.value, SAMPLE_MD5);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn checksum_with_reader() {
+    let mut reader = MainReader::new();
+    reader.set_decompress(true);
+    let checksums =
+        checksum_with("tests/sample_data_file.txt.xz", &[Algorithm::Md5], reader).unwrap();
+    assert_eq!(checksums[0], Checksum::new(Algorithm::Md5, SAMPLE_MD5));
+
+    let mut reader = MainReader::new();
+    reader.set_decompress(true);
+    let expected = Checksum::new(Algorithm::Sha256, SAMPLE_SHA256);
+    verify_checksum_with("tests/sample_data_file.txt.xz", &expected, reader).unwrap();
+
+    // Without decompression, the compressed bytes are hashed.
+    let checksums = checksum("tests/sample_data_file.txt.xz", &[Algorithm::Md5]).unwrap();
+    assert_ne!(checksums[0].value, SAMPLE_MD5);
+}
+
+#[test]
+fn checksum_reader_rereads() {
+    let data = b"somedataandsomemore\n".to_vec();
+    let mut reader = ChecksumReader::create(Cursor::new(data), &[Algorithm::Md5]);
+
+    let mut buffer = [0; 8];
+    reader.read_exact(&mut buffer).unwrap();
+    reader.seek(SeekFrom::Start(4)).unwrap();
+    let mut rest = vec![];
+    reader.read_to_end(&mut rest).unwrap();
+
+    assert_eq!(reader.hashed_size, 20);
+    reader
+        .verify(&Checksum::new(Algorithm::Md5, SAMPLE_MD5))
+        .unwrap();
+}
+
+#[test]
+fn checksum_reader_skipped_data() {
+    let data = b"somedataandsomemore\n".to_vec();
+    let mut reader = ChecksumReader::create(Cursor::new(data), &[Algorithm::Sha256]);
+
+    reader.seek(SeekFrom::Start(4)).unwrap();
+    let mut rest = vec![];
+    reader.read_to_end(&mut rest).unwrap();
+
+    assert!(reader.checksums().is_err());
+}
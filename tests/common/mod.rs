@@ -0,0 +1,37 @@
+#![allow(dead_code)]
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+/// Accept a connection and consume the request headers, returned lowercased.
+pub fn accept(server: &TcpListener) -> (TcpStream, String) {
+    let mut connection = server.accept().unwrap().0;
+    let mut request = vec![];
+    let mut byte = [0; 1];
+    while !request.ends_with(b"\r\n\r\n") {
+        connection.read_exact(&mut byte).unwrap();
+        request.push(byte[0]);
+    }
+    (
+        connection,
+        String::from_utf8(request).unwrap().to_lowercase(),
+    )
+}
+
+/// Answer one connection per response and return the received requests.
+pub fn serve(responses: Vec<String>) -> (String, thread::JoinHandle<Vec<String>>) {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/file", server.local_addr().unwrap());
+
+    let handler = thread::spawn(move || {
+        let mut requests = vec![];
+        for response in responses {
+            let (mut connection, request) = accept(&server);
+            requests.push(request);
+            connection.write_all(response.as_bytes()).unwrap();
+        }
+        requests
+    });
+    (url, handler)
+}
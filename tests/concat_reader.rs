@@ -0,0 +1,161 @@
+extern crate file_api;
+
+mod common;
+
+use common::serve;
+
+use file_api::concat_reader::{self, ConcatReader};
+use file_api::memory_reader;
+use file_api::reader::{ReadAt, Reader};
+use file_api::MainReader;
+
+use std::io::{Read, Seek, SeekFrom};
+
+fn register_segments(prefix: &str) -> Vec<String> {
+    let segments = ["some", "dataand", "somemore"];
+    segments
+        .iter()
+        .enumerate()
+        .map(|(index, data)| {
+            let filename = format!("mem://{}.part{:03}", prefix, index + 1);
+            memory_reader::register(&filename, data.as_bytes().to_vec());
+            filename
+        })
+        .collect()
+}
+
+#[test]
+fn concat_parse_segments() {
+    let segments = concat_reader::parse_segments("concat:file.part001|http://source/file.part002");
+    assert_eq!(
+        segments,
+        vec![
+            "file.part001".to_string(),
+            "http://source/file.part002".to_string()
+        ]
+    );
+}
+
+#[test]
+fn concat_exists() {
+    let segments = register_segments("exists");
+    let filename = format!("concat:{}|tests/sample_data_file.txt", segments.join("|"));
+    assert!(file_api::exists(&filename));
+
+    let filename = format!("concat:{}|tests/bad_filename.txt", segments.join("|"));
+    assert!(!file_api::exists(&filename));
+}
+
+#[test]
+fn concat_size() {
+    let segments = register_segments("size");
+    let filename = format!("concat:{}|tests/sample_data_file.txt", segments.join("|"));
+
+    let mut reader = MainReader::new();
+    reader.open(&filename).unwrap();
+    assert_eq!(reader.get_size().unwrap(), 39);
+}
+
+#[test]
+fn concat_read_across_segments() {
+    let segments = register_segments("read");
+    let mut reader = ConcatReader::new();
+    reader.open_segments(&segments).unwrap();
+
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"somedataandsomemore".to_vec());
+    assert_eq!(reader.get_position().unwrap(), 19);
+}
+
+#[test]
+fn concat_seek() {
+    let segments = register_segments("seek");
+    let mut reader = ConcatReader::new();
+    reader.open_segments(&segments).unwrap();
+
+    let position = reader.seek(SeekFrom::Start(8)).unwrap();
+    assert_eq!(position, 8);
+
+    let mut data = [0; 7];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"andsome");
+
+    let position = reader.seek(SeekFrom::Current(-13)).unwrap();
+    assert_eq!(position, 2);
+
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"meda");
+
+    let position = reader.seek(SeekFrom::End(-4)).unwrap();
+    assert_eq!(position, 15);
+
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"more");
+}
+
+#[test]
+fn concat_discover_parts() {
+    let segments = register_segments("discover");
+    assert_eq!(concat_reader::discover_parts(&segments[0]), segments);
+    assert_eq!(concat_reader::discover_parts(&segments[1]), &segments[1..]);
+
+    let mut reader = MainReader::new();
+    reader.open(&format!("concat:{}", segments[0])).unwrap();
+    assert_eq!(reader.get_size().unwrap(), 19);
+
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"somedataandsomemore".to_vec());
+
+    assert_eq!(
+        concat_reader::discover_parts("mem://discover.part"),
+        vec!["mem://discover.part".to_string()]
+    );
+}
+
+#[test]
+fn concat_empty_segment() {
+    memory_reader::register("mem://empty_segment.bin", vec![]);
+    let segments = vec![
+        "mem://empty_segment.bin".to_string(),
+        register_segments("empty_segment")[0].clone(),
+        "mem://empty_segment.bin".to_string(),
+        "tests/sample_data_file.txt".to_string(),
+    ];
+    let mut reader = ConcatReader::new();
+    reader.open_segments(&segments).unwrap();
+
+    reader.seek(SeekFrom::Start(4)).unwrap();
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"some");
+
+    let mut data = [0; 4];
+    assert_eq!(reader.read_at(8, &mut data).unwrap(), 4);
+    assert_eq!(&data, b"data");
+}
+
+#[test]
+fn concat_http_segment() {
+    let (url, handler) = serve(vec![
+        "HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\n".to_string(),
+        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-3/4\r\n\
+         Content-Length: 4\r\nConnection: close\r\n\r\nsome"
+            .to_string(),
+    ]);
+    memory_reader::register("mem://http_segment/tail", b"tail".to_vec());
+
+    let mut reader = MainReader::new();
+    reader
+        .open(&format!("concat:{}|mem://http_segment/tail", url))
+        .unwrap();
+    let mut data = String::new();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "sometail");
+
+    let requests = handler.join().unwrap();
+    assert!(requests[1].contains("range: bytes=0-3"));
+}
@@ -0,0 +1,55 @@
+extern crate file_api;
+
+use file_api::data_reader;
+use file_api::reader::Reader;
+use file_api::MainReader;
+
+use std::io::{Read, Seek, SeekFrom};
+
+#[test]
+fn data_exists() {
+    assert!(file_api::exists("data:,somedata"));
+    assert!(!file_api::exists("data:text/plain;base64,@@@"));
+}
+
+#[test]
+fn data_decode_base64() {
+    let (media_type, data) = data_reader::decode("data:image/png;base64,c29tZWRh\n dGE=").unwrap();
+    assert_eq!(media_type, "image/png");
+    assert_eq!(data, b"somedata".to_vec());
+}
+
+#[test]
+fn data_decode_percent_encoded() {
+    let (media_type, data) = data_reader::decode("data:,some%20data").unwrap();
+    assert_eq!(media_type, "text/plain;charset=US-ASCII");
+    assert_eq!(data, b"some data".to_vec());
+
+    let (media_type, _data) = data_reader::decode("data:;charset=utf-8,data").unwrap();
+    assert_eq!(media_type, "text/plain;charset=utf-8");
+
+    assert!(data_reader::decode("data:text/plain").is_err());
+}
+
+#[test]
+fn data_read_and_seek() {
+    let mut reader = MainReader::new();
+    reader
+        .open("data:application/x-subrip;base64,c29tZWRhdGFhbmRzb21lbW9yZQ==")
+        .unwrap();
+
+    assert_eq!(reader.get_media_type(), Some("application/x-subrip"));
+    assert_eq!(reader.get_size().unwrap(), 19);
+
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"some");
+    assert_eq!(reader.get_position().unwrap(), 4);
+
+    let position = reader.seek(SeekFrom::Current(4)).unwrap();
+    assert_eq!(position, 8);
+
+    let mut data = [0; 3];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"and");
+}
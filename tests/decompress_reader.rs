@@ -0,0 +1,160 @@
+#![cfg(all(feature = "gzip", feature = "zstd", feature = "xz", feature = "bzip2"))]
+
+extern crate file_api;
+extern crate flate2;
+
+use file_api::decompress_reader::{Compression, DecompressReader};
+use file_api::file_reader::FileReader;
+use file_api::memory_reader::MemoryReader;
+use file_api::reader::Reader;
+use file_api::MainReader;
+use flate2::write::GzEncoder;
+
+use std::fs;
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+
+fn open_file(filename: &str) -> FileReader {
+    let mut reader = FileReader::new();
+    reader.open(filename).unwrap();
+    reader
+}
+
+#[test]
+fn compression_from_extension() {
+    assert_eq!(
+        Compression::from_extension("subtitles.XML.GZ"),
+        Some(Compression::Gzip)
+    );
+    assert_eq!(
+        Compression::from_extension("log.zst"),
+        Some(Compression::Zstd)
+    );
+    assert_eq!(Compression::from_extension("log.xz"), Some(Compression::Xz));
+    assert_eq!(
+        Compression::from_extension("log.bz2"),
+        Some(Compression::Bzip2)
+    );
+    assert_eq!(Compression::from_extension("log.txt"), None);
+}
+
+#[test]
+fn decompress_by_extension() {
+    for filename in &[
+        "tests/sample_data_file.txt.gz",
+        "tests/sample_data_file.txt.zst",
+        "tests/sample_data_file.txt.xz",
+        "tests/sample_data_file.txt.bz2",
+    ] {
+        let mut reader = DecompressReader::detect(open_file(filename), filename).unwrap();
+
+        let mut data = String::new();
+        reader.read_to_string(&mut data).unwrap();
+        assert_eq!(data, "somedataandsomemore\n");
+        assert_eq!(reader.get_position(), 20);
+    }
+}
+
+#[test]
+fn decompress_by_magic() {
+    let data = fs::read("tests/sample_data_file.txt.zst").unwrap();
+    let reader = MemoryReader::from_data(data);
+    let mut reader = DecompressReader::detect(reader, "sidecar").unwrap();
+    assert_eq!(reader.compression, Compression::Zstd);
+
+    let mut data = [0; 8];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"somedata");
+
+    let reader = MemoryReader::from_data(b"somedata".to_vec());
+    assert!(DecompressReader::detect(reader, "sidecar").is_err());
+}
+
+#[test]
+fn decompress_seek() {
+    let filename = "tests/sample_data_file.txt.gz";
+    let mut reader = DecompressReader::create(open_file(filename), Compression::Gzip).unwrap();
+
+    let position = reader.seek(SeekFrom::Start(8)).unwrap();
+    assert_eq!(position, 8);
+
+    let mut data = [0; 3];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"and");
+
+    let position = reader.seek(SeekFrom::Current(-7)).unwrap();
+    assert_eq!(position, 4);
+
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"data");
+
+    assert!(reader.seek(SeekFrom::End(-4)).is_err());
+}
+
+#[test]
+fn decompress_seek_back_limit() {
+    let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+    for index in 0..3 * 1024 * 1024 {
+        encoder.write_all(&[index as u8]).unwrap();
+    }
+    let reader = MemoryReader::from_data(encoder.finish().unwrap());
+    let mut reader = DecompressReader::create(reader, Compression::Gzip).unwrap();
+
+    let position = reader.seek(SeekFrom::Start(3 * 1024 * 1024 - 100)).unwrap();
+    assert_eq!(position, 3 * 1024 * 1024 - 100);
+    let position = reader.seek(SeekFrom::Current(-1000)).unwrap();
+    assert_eq!(position, 3 * 1024 * 1024 - 1100);
+    let mut data = [0; 2];
+    reader.read_exact(&mut data).unwrap();
+    let expected = (3 * 1024 * 1024 - 1100) as u8;
+    assert_eq!(data, [expected, expected.wrapping_add(1)]);
+
+    let error = reader.seek(SeekFrom::Start(0)).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    assert_eq!(reader.get_position(), 3 * 1024 * 1024 - 1098);
+}
+
+#[test]
+fn decompress_main_reader() {
+    let mut reader = MainReader::new();
+    reader.set_decompress(true);
+    reader.open("tests/sample_data_file.txt.xz").unwrap();
+
+    let mut data = String::new();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "somedataandsomemore\n");
+    assert_eq!(reader.get_position().unwrap(), 20);
+    assert!(reader.get_size().is_err());
+    assert!(reader.get_stats().unwrap().bytes_delivered > 0);
+
+    reader.seek(SeekFrom::Start(4)).unwrap();
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"data");
+
+    // Files that are not compressed are read as is.
+    let mut reader = MainReader::new();
+    reader.set_decompress(true);
+    reader.open("tests/sample_data_file.txt").unwrap();
+    let mut data = String::new();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "somedataandsomemore\n");
+    assert_eq!(reader.get_size().unwrap(), 20);
+}
+
+#[test]
+fn decompress_max_end_position() {
+    let mut reader = MainReader::new();
+    reader.set_decompress(true);
+    reader.open("tests/sample_data_file.txt.gz").unwrap();
+    reader.set_max_end_position(Some(3));
+
+    let mut data = String::new();
+    reader.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "some");
+
+    reader.seek(SeekFrom::Start(3)).unwrap();
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"e");
+}
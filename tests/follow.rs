@@ -0,0 +1,150 @@
+extern crate file_api;
+
+mod common;
+
+use common::serve;
+use file_api::follow::Follow;
+use file_api::reader::Reader;
+use file_api::MainReader;
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, Read, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn growing_file(name: &str) -> String {
+    let path = env::temp_dir().join(format!("file_api_follow_{}", name));
+    let mut file = File::create(&path).unwrap();
+    file.write_all(b"somedata").unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn append_later(path: &str, follow: &Follow) -> thread::JoinHandle<()> {
+    let path = path.to_string();
+    let follow = follow.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"andsomemore\n").unwrap();
+        thread::sleep(Duration::from_millis(100));
+        follow.complete();
+    })
+}
+
+fn follow_file(name: &str, use_mmap: bool) {
+    let path = growing_file(name);
+    let follow = Follow::new(Duration::from_millis(10), Duration::from_secs(10));
+
+    let mut reader = MainReader::new();
+    reader.set_mmap(use_mmap);
+    reader.set_follow(Some(follow.clone()));
+    reader.open(&path).unwrap();
+
+    let writer = append_later(&path, &follow);
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"somedataandsomemore\n");
+
+    writer.join().unwrap();
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn follow_local_file() {
+    follow_file("read", false);
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn follow_mapped_file() {
+    follow_file("mmap", true);
+}
+
+#[test]
+fn follow_buffered() {
+    let path = growing_file("buffered");
+    let follow = Follow::new(Duration::from_millis(10), Duration::from_secs(10));
+
+    let mut reader = MainReader::new();
+    reader.set_follow(Some(follow.clone()));
+    reader.open(&path).unwrap();
+
+    assert_eq!(reader.fill_buf().unwrap(), b"somedata");
+    reader.consume(8);
+
+    let writer = append_later(&path, &follow);
+    assert_eq!(reader.fill_buf().unwrap(), b"andsomemore\n");
+    reader.consume(12);
+    assert!(reader.fill_buf().unwrap().is_empty());
+
+    writer.join().unwrap();
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn follow_idle_timeout() {
+    let path = growing_file("idle");
+    let mut reader = MainReader::new();
+    reader.set_follow(Some(Follow::new(
+        Duration::from_millis(10),
+        Duration::from_millis(100),
+    )));
+    reader.open(&path).unwrap();
+
+    let start = Instant::now();
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"somedata");
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    fs::remove_file(&path).unwrap();
+}
+
+fn head_response(size: u64) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        size
+    )
+}
+
+fn partial_response(range: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        range,
+        body.len(),
+        body
+    )
+}
+
+#[test]
+fn follow_http() {
+    let (url, handler) = serve(vec![
+        head_response(8),
+        partial_response("0-7/8", "somedata"),
+        head_response(8),
+        head_response(20),
+        partial_response("8-19/20", "andsomemore\n"),
+        head_response(20),
+    ]);
+
+    let follow = Follow::new(Duration::from_millis(10), Duration::from_secs(10));
+    let mut reader = MainReader::new();
+    reader.set_follow(Some(follow.clone()));
+    reader.open(&url).unwrap();
+    reader.set_cache_size(Some(64));
+
+    let mut buffer = [0; 32];
+    assert_eq!(reader.read(&mut buffer).unwrap(), 8);
+    assert_eq!(reader.read(&mut buffer).unwrap(), 12);
+    assert_eq!(&buffer[..12], b"andsomemore\n");
+
+    follow.complete();
+    assert_eq!(reader.read(&mut buffer).unwrap(), 0);
+    assert_eq!(reader.get_size().unwrap(), 20);
+
+    let requests = handler.join().unwrap();
+    assert!(requests[2].starts_with("head"));
+    assert!(requests[4].contains("range: bytes=8-"));
+}
@@ -0,0 +1,134 @@
+#![cfg(feature = "service-account")]
+
+extern crate base64;
+extern crate file_api;
+extern crate openssl;
+
+mod common;
+
+use common::accept;
+use file_api::error::Error;
+use file_api::gcs::{GcsAccess, GcsCredentials, ServiceAccount};
+use file_api::http_reader::HttpReader;
+use file_api::reader::Reader;
+use file_api::signer::Signer;
+use file_api::timeouts::Timeouts;
+use openssl::rsa::Rsa;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn service_account(token_uri: &str) -> GcsCredentials {
+    let key = Rsa::generate(2048).unwrap();
+    let private_key = String::from_utf8(key.private_key_to_pem().unwrap()).unwrap();
+    let account = ServiceAccount::new(
+        "reader@project.iam.gserviceaccount.com",
+        &private_key,
+        token_uri,
+    );
+    GcsCredentials {
+        endpoint: "https://storage.googleapis.com".to_string(),
+        access: GcsAccess::ServiceAccount(Arc::new(account)),
+    }
+}
+
+#[test]
+fn token_exchange() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap();
+
+    let handler = thread::spawn(move || {
+        let (mut connection, request) = accept(&server);
+        let length = request
+            .lines()
+            .find(|line| line.starts_with("content-length:"))
+            .map(|line| line["content-length:".len()..].trim().parse().unwrap())
+            .unwrap();
+        let mut body = vec![0; length];
+        connection.read_exact(&mut body).unwrap();
+
+        let token = r#"{"access_token":"ya29.service","expires_in":3600,"token_type":"Bearer"}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            token.len(),
+            token
+        );
+        connection.write_all(response.as_bytes()).unwrap();
+        let mut requests = vec![request];
+
+        for response in &[
+            "HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-3/4\r\n\
+             Content-Length: 4\r\nConnection: close\r\n\r\nsome",
+        ] {
+            let (mut connection, request) = accept(&server);
+            connection.write_all(response.as_bytes()).unwrap();
+            requests.push(request);
+        }
+        (requests, String::from_utf8(body).unwrap())
+    });
+
+    let mut reader = HttpReader::new();
+    reader.signer = Some(Signer::Gcs(service_account(&format!(
+        "http://{}/token",
+        address
+    ))));
+    reader
+        .open(&format!("http://{}/bucket/object.txt", address))
+        .unwrap();
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"some");
+
+    // The token is requested once and cached for the following requests.
+    let (requests, body) = handler.join().unwrap();
+    assert!(requests[0].starts_with("post /token "));
+    assert!(requests[1].starts_with("head /bucket/object.txt "));
+    assert!(requests[2].starts_with("get /bucket/object.txt "));
+    for request in &requests[1..] {
+        assert!(request.contains("authorization: bearer ya29.service\r\n"));
+    }
+
+    assert!(body.starts_with(
+        "grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Ajwt-bearer&assertion="
+    ));
+    let assertion = body.split("assertion=").nth(1).unwrap();
+    let parts: Vec<&str> = assertion.split('.').collect();
+    assert_eq!(parts.len(), 3);
+    let claims = base64::decode_config(parts[1], base64::URL_SAFE_NO_PAD).unwrap();
+    let claims = String::from_utf8(claims).unwrap();
+    assert!(claims.contains("\"iss\":\"reader@project.iam.gserviceaccount.com\""));
+    assert!(claims.contains("devstorage.read_only"));
+}
+
+#[test]
+fn token_timeout() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap();
+    let handler = thread::spawn(move || {
+        let _connection = accept(&server);
+        thread::sleep(Duration::from_millis(1500));
+    });
+
+    let mut reader = HttpReader::new();
+    reader.signer = Some(Signer::Gcs(service_account(&format!(
+        "http://{}/token",
+        address
+    ))));
+    reader.timeouts = Timeouts {
+        read: Some(Duration::from_millis(300)),
+        ..Default::default()
+    };
+
+    let start = Instant::now();
+    match reader.try_open(&format!("http://{}/bucket/object.txt", address)) {
+        Err(Error::Timeout(_)) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(start.elapsed() < Duration::from_secs(1));
+    handler.join().unwrap();
+}
@@ -0,0 +1,117 @@
+extern crate file_api;
+extern crate futures;
+extern crate hyper;
+
+use file_api::error::Error;
+use file_api::http_reader::{HttpReader, HttpVersion};
+use file_api::reader::{ReadAt, Reader};
+use file_api::shared_reader::SharedReader;
+use file_api::MainReader;
+
+use futures::Future;
+use hyper::header::{CONTENT_LENGTH, CONTENT_RANGE, RANGE};
+use hyper::service::service_fn_ok;
+use hyper::{Body, Method, Request, Response, Server, StatusCode, Version};
+
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+const SAMPLE_DATA: &[u8] = b"somedataandsomemore\n";
+
+fn respond(request: Request<Body>) -> Response<Body> {
+    assert_eq!(request.version(), Version::HTTP_2);
+    if request.method() == Method::HEAD {
+        return Response::builder()
+            .header(CONTENT_LENGTH, SAMPLE_DATA.len())
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    let range = request.headers()[RANGE].to_str().unwrap().to_string();
+    let mut bounds = range["bytes=".len()..].split('-');
+    let start: usize = bounds.next().unwrap().parse().unwrap();
+    let end: usize = bounds.next().unwrap().parse().unwrap();
+    let end = end.min(SAMPLE_DATA.len() - 1);
+
+    Response::builder()
+        .status(StatusCode::PARTIAL_CONTENT)
+        .header(
+            CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, SAMPLE_DATA.len()),
+        )
+        .body(Body::from(&SAMPLE_DATA[start..=end]))
+        .unwrap()
+}
+
+/// Serve `SAMPLE_DATA` with HTTP/2 only, counting the connections.
+fn serve_h2c() -> (String, Arc<AtomicUsize>) {
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into())
+        .http2_only(true)
+        .serve(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            service_fn_ok(respond)
+        });
+    let url = format!("http://{}/file", server.local_addr());
+    thread::spawn(move || hyper::rt::run(server.map_err(|error| panic!("{}", error))));
+    (url, connections)
+}
+
+#[test]
+fn h2c_prior_knowledge() {
+    let (url, connections) = serve_h2c();
+
+    let mut reader = MainReader::new();
+    reader.set_http_version(HttpVersion::Http2PriorKnowledge);
+    reader.open(&url).unwrap();
+    reader.set_cache_size(Some(8));
+
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, SAMPLE_DATA);
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn h2c_parallel_reads_share_connection() {
+    let (url, connections) = serve_h2c();
+
+    let mut reader = HttpReader::new();
+    reader.http_version = HttpVersion::Http2PriorKnowledge;
+    reader.open(&url).unwrap();
+    let reader = SharedReader::create(reader);
+
+    let threads: Vec<_> = (0..4)
+        .map(|index| {
+            let reader = reader.clone();
+            thread::spawn(move || {
+                let mut buffer = [0; 5];
+                let offset = index * 5;
+                assert_eq!(reader.read_at(offset as u64, &mut buffer).unwrap(), 5);
+                assert_eq!(&buffer, &SAMPLE_DATA[offset..offset + 5]);
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn h2_prior_knowledge_rejects_https() {
+    let mut reader = HttpReader::new();
+    reader.http_version = HttpVersion::Http2PriorKnowledge;
+    match reader.try_open("https://127.0.0.1:1/file") {
+        Err(Error::Unsupported(_)) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    let mut reader = MainReader::new();
+    reader.set_http_version(HttpVersion::Http2PriorKnowledge);
+    assert!(reader.open("HTTPS://127.0.0.1:1/file").is_err());
+}
@@ -0,0 +1,217 @@
+extern crate file_api;
+
+mod common;
+
+use common::{accept, serve};
+use file_api::error::Error;
+use file_api::http_reader::HttpReader;
+use file_api::reader::{ReadAt, Reader};
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const SAMPLE_DATA: &str = "somedataandsomemore\n";
+const SAMPLE_MD5: &str = "YWZfVw3SfefkpbknKhqktg==";
+const SAMPLE_SHA256: &str = "Q8iSLsnuwxzkJfr6lqv54PJKmMvQ+sOTQ9kYIeJho6Y=";
+
+fn head_response() -> String {
+    "HTTP/1.1 200 OK\r\nContent-Length: 20\r\nConnection: close\r\n\r\n".to_string()
+}
+
+fn partial_response(range: &str, headers: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}/20\r\n{}\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        range,
+        headers,
+        body.len(),
+        body
+    )
+}
+
+fn read_all(url: &str) -> Result<Vec<u8>, Error> {
+    let mut reader = HttpReader::new();
+    reader.open(url).unwrap();
+    let mut data = vec![0; 20];
+    reader.read_exact(&mut data)?;
+    Ok(data)
+}
+
+#[test]
+fn valid_digests() {
+    let headers = format!(
+        "Content-MD5: {}\r\nRepr-Digest: sha-256=:{}:\r\nx-amz-checksum-sha256: {}\r\n",
+        SAMPLE_MD5, SAMPLE_SHA256, SAMPLE_SHA256
+    );
+    let (url, handler) = serve(vec![
+        head_response(),
+        partial_response("0-19", &headers, SAMPLE_DATA),
+    ]);
+
+    assert_eq!(read_all(&url).unwrap(), SAMPLE_DATA.as_bytes());
+    handler.join().unwrap();
+}
+
+#[test]
+fn digest_mismatch() {
+    let headers = format!("Digest: md5={}\r\n", SAMPLE_MD5);
+    let (url, handler) = serve(vec![
+        head_response(),
+        partial_response("0-19", &headers, "somedataandsomemorE\n"),
+    ]);
+
+    let error = read_all(&url).unwrap_err();
+    match error {
+        Error::Corrupted(_) => {}
+        ref error => panic!("unexpected error {:?}", error),
+    }
+    assert!(error.is_retryable());
+    handler.join().unwrap();
+}
+
+#[test]
+fn partial_read_ignores_digest() {
+    let headers = format!("Content-MD5: {}\r\n", SAMPLE_MD5);
+    let (url, handler) = serve(vec![
+        head_response(),
+        partial_response("4-7", &headers, "data"),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    let mut buffer = [0; 4];
+    assert_eq!(reader.read_at(4, &mut buffer).unwrap(), 4);
+    assert_eq!(&buffer, b"data");
+    handler.join().unwrap();
+}
+
+#[test]
+fn truncated_range() {
+    let (url, handler) = serve(vec![
+        head_response(),
+        partial_response("0-19", "", "somedataand"),
+    ]);
+
+    let error = read_all(&url).unwrap_err();
+    match error {
+        Error::Corrupted(_) => {}
+        ref error => panic!("unexpected error {:?}", error),
+    }
+    handler.join().unwrap();
+}
+
+#[test]
+fn larger_range_is_trimmed() {
+    let (url, handler) = serve(vec![
+        head_response(),
+        partial_response("0-19", "", SAMPLE_DATA),
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: 20\r\nConnection: close\r\n\r\n{}",
+            SAMPLE_DATA
+        ),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    let mut buffer = [0; 4];
+    assert_eq!(reader.read_at(4, &mut buffer).unwrap(), 4);
+    assert_eq!(&buffer, b"data");
+    assert_eq!(reader.read_at(8, &mut buffer).unwrap(), 4);
+    assert_eq!(&buffer, b"ands");
+    handler.join().unwrap();
+}
+
+#[test]
+fn shifted_range() {
+    let (url, handler) = serve(vec![head_response(), partial_response("8-11", "", "ands")]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    let mut buffer = [0; 4];
+    assert!(reader.read_at(4, &mut buffer).is_err());
+    handler.join().unwrap();
+}
+
+#[test]
+fn unknown_length() {
+    let (url, handler) = serve(vec![
+        "HTTP/1.1 200 OK\r\nContent-Range: bytes */*\r\nConnection: close\r\n\r\n".to_string(),
+        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-3/*\r\n\
+         Content-Length: 4\r\nConnection: close\r\n\r\nsome"
+            .to_string(),
+        partial_response("4-7", "", "data"),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    assert_eq!(reader.file_size, None);
+
+    let mut buffer = [0; 4];
+    reader.read_exact(&mut buffer).unwrap();
+    assert_eq!(&buffer, b"some");
+    assert_eq!(reader.file_size, None);
+
+    reader.read_exact(&mut buffer).unwrap();
+    assert_eq!(&buffer, b"data");
+    assert_eq!(reader.get_size().unwrap(), 20);
+    handler.join().unwrap();
+}
+
+#[test]
+fn length_only_content_range() {
+    let (url, handler) = serve(vec![
+        "HTTP/1.1 200 OK\r\nContent-Range: bytes */20\r\nConnection: close\r\n\r\n".to_string(),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    assert_eq!(reader.get_size().unwrap(), 20);
+    handler.join().unwrap();
+}
+
+#[test]
+fn ignored_range_past_end() {
+    let (url, handler) = serve(vec![
+        "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n".to_string(),
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: 20\r\nConnection: close\r\n\r\n{}",
+            SAMPLE_DATA
+        ),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    let mut buffer = [0; 4];
+    assert_eq!(reader.read_at(30, &mut buffer).unwrap(), 0);
+    handler.join().unwrap();
+}
+
+#[test]
+fn ignored_range_stops_at_end() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/file", server.local_addr().unwrap());
+    let handler = thread::spawn(move || {
+        let (mut connection, _) = accept(&server);
+        connection.write_all(head_response().as_bytes()).unwrap();
+        drop(connection);
+
+        // The whole content, of which the remaining bytes never come.
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000000\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        connection.write_all(SAMPLE_DATA.as_bytes()).unwrap();
+        thread::sleep(Duration::from_secs(2));
+    });
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    let start = Instant::now();
+    let mut buffer = [0; 4];
+    assert_eq!(reader.read_at(4, &mut buffer).unwrap(), 4);
+    assert_eq!(&buffer, b"data");
+    assert!(start.elapsed() < Duration::from_secs(1));
+    handler.join().unwrap();
+}
@@ -0,0 +1,146 @@
+extern crate file_api;
+
+use file_api::listing::{self, Entry};
+use file_api::{azure, gcs, memory_reader};
+
+use std::time::{Duration, UNIX_EPOCH};
+
+#[test]
+fn list_local_directory() {
+    let entries = file_api::list("tests").unwrap();
+
+    let sample = entries
+        .iter()
+        .find(|entry| entry.name == "sample_data_file.txt")
+        .unwrap();
+    assert_eq!(sample.size, Some(20));
+    assert!(sample.modified.is_some());
+    assert!(!sample.is_dir);
+
+    assert!(file_api::list("tests/bad_directory").is_err());
+}
+
+#[test]
+fn list_glob_local_directory() {
+    let entries = file_api::list_glob("tests", "sample_archive.*").unwrap();
+    let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, vec!["sample_archive.tar", "sample_archive.zip"]);
+}
+
+#[test]
+fn list_memory_registry() {
+    memory_reader::register("mem://listing/first.txt", b"some".to_vec());
+    memory_reader::register("mem://listing/folder/second.txt", b"data".to_vec());
+
+    let entries = file_api::list("mem://listing").unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            Entry {
+                name: "first.txt".to_string(),
+                size: Some(4),
+                modified: None,
+                is_dir: false,
+            },
+            Entry {
+                name: "folder".to_string(),
+                size: None,
+                modified: None,
+                is_dir: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_html_index() {
+    let body = r#"<html><body><h1>Index of /media/</h1><pre>
+<a href="?C=N;O=D">Name</a> <a href="../">Parent Directory</a>
+<a href="master%201.mxf">master 1.mxf</a>   01-Jan-2020 00:00  12M
+<a href="subtitles/">subtitles/</a>         01-Jan-2020 00:00    -
+<a href="http://other.host/file.mxf">external</a>
+</pre></body></html>"#;
+
+    let entries = listing::parse_html_index(body);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, "master 1.mxf");
+    assert!(!entries[0].is_dir);
+    assert_eq!(entries[1].name, "subtitles");
+    assert!(entries[1].is_dir);
+}
+
+#[test]
+fn parse_propfind() {
+    let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<D:multistatus xmlns:D="DAV:">
+  <D:response><D:href>/media/</D:href>
+    <D:propstat><D:prop><D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat>
+  </D:response>
+  <D:response><D:href>http://host/media/master.mxf</D:href>
+    <D:propstat><D:prop><D:resourcetype/>
+      <D:getcontentlength>19</D:getcontentlength>
+      <D:getlastmodified>Wed, 01 Jan 2020 00:00:00 GMT</D:getlastmodified>
+    </D:prop></D:propstat>
+  </D:response>
+  <D:response><D:href>/media/sub%20folder/</D:href>
+    <D:propstat><D:prop><D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat>
+  </D:response>
+</D:multistatus>"#;
+
+    let entries = listing::parse_propfind(body, "/media/");
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, "master.mxf");
+    assert_eq!(entries[0].size, Some(19));
+    assert_eq!(
+        entries[0].modified,
+        Some(UNIX_EPOCH + Duration::from_secs(1_577_836_800))
+    );
+    assert!(!entries[0].is_dir);
+    assert_eq!(entries[1].name, "sub folder");
+    assert!(entries[1].is_dir);
+}
+
+#[test]
+fn parse_azure_blob_list() {
+    let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ContainerName="media"><Prefix>ingest/</Prefix><Blobs>
+<Blob><Name>ingest/master.mxf</Name><Properties>
+<Last-Modified>Wed, 01 Jan 2020 00:00:00 GMT</Last-Modified><Content-Length>19</Content-Length>
+</Properties></Blob>
+<BlobPrefix><Name>ingest/proxies/</Name></BlobPrefix>
+</Blobs><NextMarker>2!48!marker</NextMarker></EnumerationResults>"#;
+
+    let (entries, marker) = azure::parse_blob_list(body, "ingest/");
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, "master.mxf");
+    assert_eq!(entries[0].size, Some(19));
+    assert_eq!(
+        entries[0].modified,
+        Some(UNIX_EPOCH + Duration::from_secs(1_577_836_800))
+    );
+    assert_eq!(entries[1].name, "proxies");
+    assert!(entries[1].is_dir);
+    assert_eq!(marker, Some("2!48!marker".to_string()));
+}
+
+#[test]
+fn parse_gcs_bucket_list() {
+    let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://doc.s3.amazonaws.com/2006-03-01">
+<Name>bucket</Name><Prefix>ingest/</Prefix><IsTruncated>false</IsTruncated>
+<Contents><Key>ingest/master.mxf</Key><LastModified>2020-01-01T00:00:00.000Z</LastModified><Size>19</Size></Contents>
+<CommonPrefixes><Prefix>ingest/proxies/</Prefix></CommonPrefixes>
+</ListBucketResult>"#;
+
+    let (entries, marker) = gcs::parse_bucket_list(body, "ingest/");
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, "master.mxf");
+    assert_eq!(entries[0].size, Some(19));
+    assert_eq!(
+        entries[0].modified,
+        Some(UNIX_EPOCH + Duration::from_secs(1_577_836_800))
+    );
+    assert_eq!(entries[1].name, "proxies");
+    assert!(entries[1].is_dir);
+    assert_eq!(marker, None);
+}
@@ -0,0 +1,105 @@
+extern crate bytes;
+extern crate file_api;
+
+use file_api::memory_reader::{self, MemoryReader};
+use file_api::reader::Reader;
+use file_api::MainReader;
+
+use bytes::Bytes;
+
+use std::io::{BufRead, Read, Seek, SeekFrom};
+
+#[test]
+fn memory_exists() {
+    memory_reader::register("mem://exists.txt", b"somedata".to_vec());
+    assert!(file_api::exists("mem://exists.txt"));
+    assert!(!file_api::exists("mem://bad_filename.txt"));
+
+    assert!(memory_reader::unregister("mem://exists.txt"));
+    assert!(!file_api::exists("mem://exists.txt"));
+}
+
+#[test]
+fn memory_size() {
+    let mut reader = MemoryReader::from_data(b"somedataandsomemore".to_vec());
+
+    let size = reader.get_size().unwrap();
+    assert_eq!(size, 19);
+}
+
+#[test]
+fn memory_read_data() {
+    memory_reader::register("mem://read_data.txt", b"somedataandsomemore".to_vec());
+
+    let mut reader = MainReader::new();
+    reader.open("mem://read_data.txt").unwrap();
+
+    let position = reader.get_position().unwrap();
+    assert_eq!(position, 0);
+
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"some");
+    assert_eq!(reader.get_position().unwrap(), 4);
+
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"data");
+    assert_eq!(reader.get_position().unwrap(), 8);
+}
+
+#[test]
+fn memory_seek() {
+    let mut reader = MemoryReader::from_data(b"somedataandsomemore".to_vec());
+
+    let position = reader.seek(SeekFrom::Current(4)).unwrap();
+    assert_eq!(position, 4);
+
+    let position = reader.seek(SeekFrom::End(-4)).unwrap();
+    assert_eq!(position, 15);
+
+    let mut data = [0; 8];
+    let size = reader.read(&mut data).unwrap();
+    assert_eq!(size, 4);
+    assert_eq!(&data[..size], b"more");
+
+    assert!(reader.seek(SeekFrom::Current(-20)).is_err());
+}
+
+#[test]
+fn memory_max_end_position() {
+    let mut reader = MemoryReader::from_data(b"somedataandsomemore".to_vec());
+    reader.set_max_end_position(Some(7));
+
+    let mut data = [0; 12];
+    let size = reader.read(&mut data).unwrap();
+    assert_eq!(size, 8);
+    assert_eq!(&data[..size], b"somedata");
+
+    let size = reader.read(&mut data).unwrap();
+    assert_eq!(size, 0);
+}
+
+#[test]
+fn memory_cache_size() {
+    let mut reader = MemoryReader::from_data(b"somedataandsomemore".to_vec());
+    reader.set_cache_size(Some(8));
+    reader.set_max_end_position(Some(std::u64::MAX));
+
+    let mut data = [0; 12];
+    assert_eq!(reader.read(&mut data).unwrap(), 8);
+    assert_eq!(&data[..8], b"somedata");
+    assert_eq!(reader.fill_buf().unwrap(), b"andsomem");
+}
+
+#[test]
+fn memory_bytes_source() {
+    memory_reader::register("mem://bytes.txt", Bytes::from_static(b"somedata"));
+
+    let mut reader = MainReader::new();
+    reader.open("mem://bytes.txt").unwrap();
+
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"somedata");
+}
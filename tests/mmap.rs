@@ -0,0 +1,69 @@
+#![cfg(feature = "mmap")]
+
+extern crate file_api;
+
+use file_api::file_reader::FileReader;
+use file_api::reader::{ReadAt, Reader};
+use file_api::MainReader;
+
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+
+#[test]
+fn mmap_read_and_seek() {
+    let expected = fs::read("tests/sample_data_file.txt").unwrap();
+
+    let mut reader = FileReader::new();
+    reader.use_mmap = true;
+    reader.open("tests/sample_data_file.txt").unwrap();
+    assert!(reader.mapped().is_some());
+    assert_eq!(reader.get_size().unwrap(), 20);
+
+    let mut data = [0; 4];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(data, expected[0..4]);
+
+    reader.seek(SeekFrom::End(-4)).unwrap();
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, &expected[16..]);
+    assert_eq!(reader.get_position().unwrap(), 20);
+
+    let mut data = [0; 3];
+    assert_eq!(reader.read_at(8, &mut data).unwrap(), 3);
+    assert_eq!(data, expected[8..11]);
+    assert_eq!(reader.get_slice(4, 4), Some(&expected[4..8]));
+    assert_eq!(reader.get_slice(18, 10), Some(&expected[18..]));
+    assert_eq!(reader.get_slice(4, usize::max_value()), Some(&expected[4..]));
+    assert!(reader.seek(SeekFrom::Current(-100)).is_err());
+}
+
+#[test]
+fn mmap_fallback_for_empty_file() {
+    let path = "tests/empty_mmap_file.bin";
+    File::create(path).unwrap();
+
+    let mut reader = FileReader::new();
+    reader.use_mmap = true;
+    let result = reader.open(path);
+    fs::remove_file(path).unwrap();
+    result.unwrap();
+
+    assert!(reader.mapped().is_none());
+    assert_eq!(reader.get_slice(0, 4), None);
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert!(data.is_empty());
+}
+
+#[test]
+fn main_reader_mmap() {
+    let mut reader = MainReader::new();
+    reader.set_mmap(true);
+    reader.open("tests/sample_data_file.txt").unwrap();
+    assert!(reader.file_reader.as_ref().unwrap().mapped().is_some());
+
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, fs::read("tests/sample_data_file.txt").unwrap());
+}
@@ -0,0 +1,156 @@
+extern crate file_api;
+
+mod common;
+
+use common::{accept, serve};
+use file_api::http_reader::HttpReader;
+use file_api::multipart::{byteranges_boundary, parse_byteranges, BytePart};
+use file_api::reader::Reader;
+
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const HEAD_RESPONSE: &str = "HTTP/1.1 200 OK\r\nContent-Length: 20\r\nConnection: close\r\n\r\n";
+
+const MULTIPART_BODY: &str = "--THIS_STRING_SEPARATES\r\n\
+Content-Type: application/octet-stream\r\n\
+Content-Range: bytes 2-4/20\r\n\
+\r\n\
+234\r\n\
+--THIS_STRING_SEPARATES\r\n\
+Content-Type: application/octet-stream\r\n\
+Content-Range: bytes 10-13/20\r\n\
+\r\n\
+abcd\r\n\
+--THIS_STRING_SEPARATES--\r\n";
+
+fn partial_response(range: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}/20\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        range,
+        body.len(),
+        body
+    )
+}
+
+#[test]
+fn parse_boundary() {
+    assert_eq!(
+        byteranges_boundary("multipart/byteranges; boundary=THIS_STRING_SEPARATES"),
+        Some("THIS_STRING_SEPARATES".to_string())
+    );
+    assert_eq!(
+        byteranges_boundary("multipart/byteranges; boundary=\"quoted\""),
+        Some("quoted".to_string())
+    );
+    assert_eq!(byteranges_boundary("application/octet-stream"), None);
+}
+
+#[test]
+fn parse_parts() {
+    let parts = parse_byteranges(MULTIPART_BODY.as_bytes(), "THIS_STRING_SEPARATES").unwrap();
+    assert_eq!(
+        parts,
+        [
+            BytePart {
+                start: 2,
+                data: b"234".to_vec(),
+            },
+            BytePart {
+                start: 10,
+                data: b"abcd".to_vec(),
+            },
+        ]
+    );
+
+    assert!(parse_byteranges(b"garbage", "THIS_STRING_SEPARATES").is_err());
+
+    let truncated = MULTIPART_BODY.replace("abcd", "abc");
+    assert!(parse_byteranges(truncated.as_bytes(), "THIS_STRING_SEPARATES").is_err());
+}
+
+#[test]
+fn read_ranges_multipart() {
+    let (url, handler) = serve(vec![
+        HEAD_RESPONSE.to_string(),
+        format!(
+            "HTTP/1.1 206 Partial Content\r\n\
+             Content-Type: multipart/byteranges; boundary=THIS_STRING_SEPARATES\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            MULTIPART_BODY.len(),
+            MULTIPART_BODY
+        ),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    let data = reader.read_ranges(&[(2, 3), (10, 4), (30, 2)]).unwrap();
+    assert_eq!(data, [b"234".to_vec(), b"abcd".to_vec(), vec![]]);
+
+    let requests = handler.join().unwrap();
+    assert!(requests[1].contains("range: bytes=2-4,10-13"));
+}
+
+#[test]
+fn read_ranges_coalesced() {
+    let (url, handler) = serve(vec![
+        HEAD_RESPONSE.to_string(),
+        partial_response("2-13", "23456789abcd"),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    let data = reader.read_ranges(&[(2, 3), (10, 4)]).unwrap();
+    assert_eq!(data, [b"234".to_vec(), b"abcd".to_vec()]);
+    handler.join().unwrap();
+}
+
+#[test]
+fn read_ranges_fallback() {
+    let (url, handler) = serve(vec![
+        HEAD_RESPONSE.to_string(),
+        "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            .to_string(),
+        partial_response("2-4", "234"),
+        partial_response("10-13", "abcd"),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    let data = reader.read_ranges(&[(2, 3), (10, 4)]).unwrap();
+    assert_eq!(data, [b"234".to_vec(), b"abcd".to_vec()]);
+
+    let requests = handler.join().unwrap();
+    assert!(requests[2].contains("range: bytes=2-4\r\n"));
+    assert!(requests[3].contains("range: bytes=10-13\r\n"));
+}
+
+#[test]
+fn read_ranges_ignored() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/file", server.local_addr().unwrap());
+    let handler = thread::spawn(move || {
+        let (mut connection, _) = accept(&server);
+        connection.write_all(HEAD_RESPONSE.as_bytes()).unwrap();
+        drop(connection);
+
+        // The whole content, of which the remaining bytes never come.
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000000\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        connection.write_all(b"0123456789abcdefghij").unwrap();
+        thread::sleep(Duration::from_secs(2));
+    });
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    let start = Instant::now();
+    let data = reader.read_ranges(&[(2, 3), (10, 4)]).unwrap();
+    assert_eq!(data, [b"234".to_vec(), b"abcd".to_vec()]);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    handler.join().unwrap();
+}
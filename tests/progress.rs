@@ -0,0 +1,97 @@
+extern crate file_api;
+
+mod common;
+
+use common::serve;
+use file_api::progress::{Progress, ProgressObserver};
+use file_api::reader::Reader;
+use file_api::MainReader;
+
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+struct Recorder {
+    reports: Mutex<Vec<Progress>>,
+}
+
+impl ProgressObserver for Recorder {
+    fn on_progress(&self, progress: &Progress) {
+        self.reports.lock().unwrap().push(progress.clone());
+    }
+}
+
+#[test]
+fn report_every_read() {
+    let recorder = Arc::new(Recorder::default());
+    let mut reader = MainReader::new();
+    reader.open("tests/sample_data_file.txt").unwrap();
+    reader.set_progress_observer(recorder.clone(), Duration::from_secs(0));
+
+    let mut data = [0; 5];
+    for _ in 0..4 {
+        reader.read_exact(&mut data).unwrap();
+    }
+    assert_eq!(reader.read(&mut data).unwrap(), 0);
+
+    let reports = recorder.reports.lock().unwrap();
+    let positions: Vec<u64> = reports.iter().map(|report| report.position).collect();
+    assert_eq!(positions, [5, 10, 15, 20]);
+    assert!(reports.iter().all(|report| report.size == Some(20)));
+}
+
+#[test]
+fn report_at_end_of_stream() {
+    let recorder = Arc::new(Recorder::default());
+    let mut reader = MainReader::new();
+    reader.open("tests/sample_data_file.txt").unwrap();
+    reader.set_progress_observer(recorder.clone(), Duration::from_secs(3_600));
+
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+
+    let reports = recorder.reports.lock().unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].position, 20);
+    assert_eq!(reports[0].size, Some(20));
+}
+
+#[test]
+fn size_looked_up_once() {
+    let partial_response = |range: &str, body: &str| {
+        format!(
+            "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}/*\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            range,
+            body.len(),
+            body
+        )
+    };
+    let (url, handler) = serve(vec![
+        "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n".to_string(),
+        partial_response("0-4", "somed"),
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            .to_string(),
+        partial_response("5-9", "ataan"),
+    ]);
+
+    let recorder = Arc::new(Recorder::default());
+    let mut reader = MainReader::new();
+    reader.open(&url).unwrap();
+    reader.set_progress_observer(recorder.clone(), Duration::from_secs(0));
+
+    let mut data = [0; 5];
+    reader.read_exact(&mut data).unwrap();
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"ataan");
+
+    let reports = recorder.reports.lock().unwrap();
+    let positions: Vec<u64> = reports.iter().map(|report| report.position).collect();
+    assert_eq!(positions, [5, 10]);
+    assert!(reports.iter().all(|report| report.size.is_none()));
+
+    let requests = handler.join().unwrap();
+    assert_eq!(requests.len(), 4);
+    assert!(requests[2].contains("range: bytes=0-0"));
+}
@@ -0,0 +1,89 @@
+extern crate file_api;
+
+use file_api::cancellation::CancellationToken;
+use file_api::error::{Error, TimeoutKind};
+use file_api::rate_limiter::RateLimiter;
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn burst_then_wait() {
+    let rate_limiter = RateLimiter::new(1_000, 500);
+
+    assert_eq!(rate_limiter.reserve(500), Duration::from_secs(0));
+
+    let wait = rate_limiter.reserve(500);
+    assert!(wait > Duration::from_millis(400));
+    assert!(wait <= Duration::from_millis(500));
+
+    let wait = rate_limiter.reserve(500);
+    assert!(wait > Duration::from_millis(900));
+}
+
+#[test]
+fn chunk_size() {
+    let rate_limiter = RateLimiter::new(1_000, 500);
+    assert_eq!(rate_limiter.chunk_size(100), 100);
+    assert_eq!(rate_limiter.chunk_size(4_096), 500);
+
+    let rate_limiter = RateLimiter::new(0, 0);
+    assert_eq!(rate_limiter.chunk_size(4_096), 4_096);
+    assert_eq!(rate_limiter.reserve(1_000_000), Duration::from_secs(0));
+}
+
+#[test]
+fn shared_budget() {
+    let rate_limiter = Arc::new(RateLimiter::new(10_000, 1_000));
+    let start = Instant::now();
+
+    let handlers: Vec<_> = (0..4)
+        .map(|_| {
+            let rate_limiter = rate_limiter.clone();
+            thread::spawn(move || {
+                for _ in 0..5 {
+                    rate_limiter.acquire(100);
+                }
+            })
+        })
+        .collect();
+    for handler in handlers {
+        handler.join().unwrap();
+    }
+
+    // 2000 bytes with a burst of 1000 at 10000 bytes/s take at least 100ms.
+    assert!(start.elapsed() >= Duration::from_millis(90));
+}
+
+#[test]
+fn interrupted_wait() {
+    let rate_limiter = RateLimiter::new(1_000, 0);
+    let cancellation = CancellationToken::new();
+
+    let start = Instant::now();
+    let deadline = start + Duration::from_millis(100);
+    match rate_limiter.acquire_with(10_000, &cancellation, Some(deadline)) {
+        Err(Error::Timeout(TimeoutKind::Deadline)) => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    let token = cancellation.clone();
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        token.cancel();
+    });
+    match rate_limiter.acquire_with(10_000, &cancellation, None) {
+        Err(Error::Cancelled) => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(start.elapsed() < Duration::from_secs(2));
+    canceller.join().unwrap();
+
+    let rate_limiter = RateLimiter::new(1_000, 500);
+    let throttled = rate_limiter
+        .acquire_with(600, &CancellationToken::new(), None)
+        .unwrap();
+    assert!(throttled >= Duration::from_millis(90));
+}
@@ -0,0 +1,86 @@
+extern crate file_api;
+
+use file_api::memory_reader;
+use file_api::reader::{ReadAt, Reader};
+use file_api::shared_reader::SharedReader;
+use file_api::MainReader;
+
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::thread;
+
+fn read_at_all(reader: &MainReader, offset: u64, size: usize) -> Vec<u8> {
+    let mut data = vec![0; size];
+    let readed_size = reader.read_at(offset, &mut data).unwrap();
+    data.truncate(readed_size);
+    data
+}
+
+#[test]
+fn read_at_keeps_position() {
+    let expected = fs::read("tests/sample_data_file.txt").unwrap();
+
+    for filename in &[
+        "tests/sample_data_file.txt",
+        "tests/sample_archive.zip#sample_data_file.txt",
+        "tests/sample_archive.tar#sample_data_file.txt",
+    ] {
+        let mut reader = MainReader::new();
+        reader.open(filename).unwrap();
+        reader.seek(SeekFrom::Start(2)).unwrap();
+
+        assert_eq!(read_at_all(&reader, 5, 4), &expected[5..9]);
+        assert_eq!(read_at_all(&reader, 18, 10), &expected[18..]);
+        assert!(read_at_all(&reader, 100, 4).is_empty());
+        assert_eq!(reader.get_position().unwrap(), 2);
+    }
+}
+
+#[test]
+fn read_at_memory_and_concat() {
+    memory_reader::register("mem://read_at/part1", b"some".to_vec());
+    memory_reader::register("mem://read_at/part2", b"data".to_vec());
+
+    let mut reader = MainReader::new();
+    reader
+        .open("concat:mem://read_at/part1|mem://read_at/part2")
+        .unwrap();
+    assert_eq!(read_at_all(&reader, 1, 3), b"ome");
+    assert_eq!(read_at_all(&reader, 5, 10), b"ata");
+
+    let mut reader = MainReader::new();
+    reader.open("data:,hello").unwrap();
+    assert_eq!(read_at_all(&reader, 1, 3), b"ell");
+}
+
+#[test]
+fn shared_between_threads() {
+    let expected = fs::read("tests/sample_data_file.txt").unwrap();
+
+    let mut reader = MainReader::new();
+    reader.open("tests/sample_data_file.txt").unwrap();
+    let shared = SharedReader::create(reader);
+    assert_eq!(shared.size, Some(20));
+
+    let handlers: Vec<_> = (0..4)
+        .map(|index| {
+            let mut shared = shared.clone();
+            thread::spawn(move || {
+                let mut data = [0; 5];
+                shared.seek(SeekFrom::Start(index * 5)).unwrap();
+                shared.read_exact(&mut data).unwrap();
+                data
+            })
+        })
+        .collect();
+
+    for (index, handler) in handlers.into_iter().enumerate() {
+        assert_eq!(handler.join().unwrap(), expected[index * 5..index * 5 + 5]);
+    }
+
+    let mut shared = shared.clone();
+    shared.seek(SeekFrom::End(-4)).unwrap();
+    let mut data = vec![];
+    shared.read_to_end(&mut data).unwrap();
+    assert_eq!(data, &expected[16..]);
+}
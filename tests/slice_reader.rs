@@ -0,0 +1,82 @@
+extern crate file_api;
+
+use file_api::file_reader::FileReader;
+use file_api::memory_reader::MemoryReader;
+use file_api::reader::Reader;
+use file_api::slice_reader::SliceReader;
+
+use std::io::{Read, Seek, SeekFrom};
+
+#[test]
+fn slice_size() {
+    let reader = MemoryReader::from_data(b"somedataandsomemore".to_vec());
+    let mut slice = SliceReader::create(reader, 4, 11);
+
+    assert_eq!(slice.get_size().unwrap(), 7);
+    assert_eq!(slice.get_position().unwrap(), 0);
+}
+
+#[test]
+fn slice_read_data() {
+    let mut reader = FileReader::new();
+    reader.open("tests/sample_data_file.txt").unwrap();
+    let mut slice = SliceReader::create(reader, 4, 11);
+
+    let mut data = vec![];
+    slice.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"dataand".to_vec());
+    assert_eq!(slice.get_position().unwrap(), 7);
+}
+
+#[test]
+fn slice_seek() {
+    let reader = MemoryReader::from_data(b"somedataandsomemore".to_vec());
+    let mut slice = SliceReader::create(reader, 4, 15);
+
+    let position = slice.seek(SeekFrom::Start(4)).unwrap();
+    assert_eq!(position, 4);
+
+    let mut data = [0; 3];
+    slice.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"and");
+
+    let position = slice.seek(SeekFrom::End(-4)).unwrap();
+    assert_eq!(position, 7);
+
+    let mut data = [0; 8];
+    let size = slice.read(&mut data).unwrap();
+    assert_eq!(&data[..size], b"some");
+
+    assert!(slice.seek(SeekFrom::Current(-12)).is_err());
+}
+
+#[test]
+fn slice_max_end_position() {
+    let reader = MemoryReader::from_data(b"somedataandsomemore".to_vec());
+    let mut slice = SliceReader::create(reader, 4, 15);
+    slice.set_max_end_position(Some(3));
+
+    let mut data = vec![];
+    slice.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"data".to_vec());
+}
+
+#[test]
+fn slice_bounds() {
+    let reader = MemoryReader::from_data(b"somedataandsomemore".to_vec());
+    let mut slice = SliceReader::create(reader, 4, 4);
+    assert_eq!(slice.get_size().unwrap(), 0);
+    let mut data = vec![];
+    slice.read_to_end(&mut data).unwrap();
+    assert!(data.is_empty());
+
+    let reader = MemoryReader::from_data(b"somedataandsomemore".to_vec());
+    let mut slice = SliceReader::create(reader, 15, 100);
+    assert_eq!(slice.get_size().unwrap(), 4);
+    slice.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"more".to_vec());
+
+    let mut slice: SliceReader<FileReader> = SliceReader::new();
+    slice.open("tests/sample_data_file.txt").unwrap();
+    assert_eq!(slice.get_size().unwrap(), 20);
+}
@@ -0,0 +1,120 @@
+extern crate file_api;
+
+mod common;
+
+use common::accept;
+use file_api::error::{Error, TimeoutKind};
+use file_api::memory_reader;
+use file_api::reader::Reader;
+use file_api::stat;
+use file_api::timeouts::Timeouts;
+use file_api::MainReader;
+
+use std::io::{self, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn stat_file() {
+    let metadata = stat("tests/sample_data_file.txt").unwrap().unwrap();
+    assert_eq!(metadata.size, Some(20));
+    assert!(metadata.modified.is_some());
+    assert!(!metadata.is_dir);
+
+    let metadata = stat("tests").unwrap().unwrap();
+    assert!(metadata.is_dir);
+
+    assert_eq!(stat("tests/missing_file.txt").unwrap(), None);
+}
+
+#[test]
+fn stat_memory_and_data() {
+    memory_reader::register("mem://stat/data.bin", b"0123456789".to_vec());
+    let metadata = stat("mem://stat/data.bin").unwrap().unwrap();
+    assert_eq!(metadata.size, Some(10));
+    assert_eq!(stat("mem://stat/missing.bin").unwrap(), None);
+
+    let metadata = stat("data:text/plain,hello").unwrap().unwrap();
+    assert_eq!(metadata.size, Some(5));
+    assert_eq!(metadata.content_type, Some("text/plain".to_string()));
+}
+
+#[test]
+fn stat_concat() {
+    memory_reader::register("mem://stat/part1", b"some".to_vec());
+    memory_reader::register("mem://stat/part2", b"data".to_vec());
+
+    let metadata = stat("concat:mem://stat/part1|mem://stat/part2")
+        .unwrap()
+        .unwrap();
+    assert_eq!(metadata.size, Some(8));
+    assert_eq!(
+        stat("concat:mem://stat/part1|mem://stat/part3").unwrap(),
+        None
+    );
+}
+
+#[test]
+fn stat_archive_member() {
+    let metadata = stat("tests/sample_archive.zip#sample_data_file.txt")
+        .unwrap()
+        .unwrap();
+    assert_eq!(metadata.size, Some(20));
+    let metadata = stat("tests/sample_archive.tar#sample_data_file.txt")
+        .unwrap()
+        .unwrap();
+    assert_eq!(metadata.size, Some(20));
+
+    assert_eq!(stat("tests/sample_archive.zip#missing.txt").unwrap(), None);
+    assert_eq!(stat("tests/missing_archive.tar#missing.txt").unwrap(), None);
+}
+
+#[test]
+fn retryable_errors() {
+    assert!(Error::Request("connection refused".to_string()).is_retryable());
+    assert!(Error::Status(503).is_retryable());
+    assert!(Error::Status(429).is_retryable());
+    assert!(!Error::Status(403).is_retryable());
+    assert!(Error::Io(io::Error::new(io::ErrorKind::TimedOut, "timeout")).is_retryable());
+    assert!(!Error::Other("invalid url".to_string()).is_retryable());
+}
+
+#[test]
+fn stat_with_reader_timeouts() {
+    let mut reader = MainReader::new();
+    reader.set_timeouts(Timeouts {
+        deadline: Some(Duration::from_secs(0)),
+        ..Default::default()
+    });
+    match reader.stat("http://127.0.0.1:1/file") {
+        Err(Error::Timeout(TimeoutKind::Deadline)) => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    // The archive exists, then hangs when opened to look for the member.
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/archive.tar", server.local_addr().unwrap());
+    let handler = thread::spawn(move || {
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1024\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        drop(connection);
+
+        let connection = accept(&server);
+        thread::sleep(Duration::from_secs(1));
+        drop(connection);
+    });
+
+    let mut reader = MainReader::new();
+    reader.set_timeouts(Timeouts {
+        read: Some(Duration::from_millis(200)),
+        ..Default::default()
+    });
+    match reader.stat(&format!("{}#member.txt", url)) {
+        Err(Error::Timeout(TimeoutKind::Request)) => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+    handler.join().unwrap();
+}
@@ -0,0 +1,98 @@
+extern crate file_api;
+
+mod common;
+
+use common::serve;
+use file_api::memory_reader;
+use file_api::reader::Reader;
+use file_api::stats::{Metrics, RequestEvent};
+use file_api::MainReader;
+
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default)]
+struct Recorder {
+    requests: Mutex<Vec<(String, u64, u64)>>,
+}
+
+impl Metrics for Recorder {
+    fn on_request(&self, event: &RequestEvent) {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((event.method.to_string(), event.position, event.size));
+    }
+}
+
+#[test]
+fn file_stats() {
+    let mut reader = MainReader::new();
+    reader.open("tests/sample_data_file.txt").unwrap();
+
+    let mut data = [0; 20];
+    reader.read_exact(&mut data).unwrap();
+
+    let stats = reader.get_stats().unwrap();
+    assert!(stats.requests >= 1);
+    assert_eq!(stats.bytes_fetched, 20);
+    assert_eq!(stats.bytes_delivered, 20);
+}
+
+#[test]
+fn http_stats_and_metrics() {
+    let (url, handler) = serve(vec![
+        "HTTP/1.1 200 OK\r\nContent-Length: 10\r\nConnection: close\r\n\r\n".to_string(),
+        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-9/10\r\n\
+         Content-Length: 10\r\nConnection: close\r\n\r\n0123456789"
+            .to_string(),
+    ]);
+
+    let recorder = Arc::new(Recorder::default());
+    let mut reader = MainReader::new();
+    reader.set_metrics(Some(recorder.clone()));
+    reader.open(&url).unwrap();
+    reader.set_cache_size(Some(10));
+
+    let mut data = [0; 5];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"01234");
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(&data, b"56789");
+    handler.join().unwrap();
+
+    let stats = reader.get_stats().unwrap();
+    assert_eq!(stats.requests, 2);
+    assert_eq!(stats.failures, 0);
+    assert_eq!(stats.retries, 0);
+    assert_eq!(stats.bytes_fetched, 10);
+    assert_eq!(stats.bytes_delivered, 10);
+    assert_eq!(stats.cache_misses, 1);
+    assert_eq!(stats.cache_hits, 1);
+
+    let requests = recorder.requests.lock().unwrap();
+    assert_eq!(
+        *requests,
+        [("HEAD".to_string(), 0, 0), ("GET".to_string(), 0, 10)]
+    );
+}
+
+#[test]
+fn nested_reader_stats() {
+    memory_reader::register("mem://stats/first", b"0123".to_vec());
+    memory_reader::register("mem://stats/second", b"456789".to_vec());
+
+    let mut reader = MainReader::new();
+    reader
+        .open("concat:mem://stats/first|mem://stats/second")
+        .unwrap();
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(reader.get_stats().unwrap().bytes_delivered, 10);
+
+    let mut reader = MainReader::new();
+    reader.open("data:,hello").unwrap();
+    let mut data = [0; 3];
+    reader.read_exact(&mut data).unwrap();
+    assert_eq!(reader.get_stats().unwrap().bytes_delivered, 3);
+}
@@ -0,0 +1,132 @@
+extern crate file_api;
+
+mod common;
+
+use common::accept;
+use file_api::error::{Error, TimeoutKind};
+use file_api::http_reader;
+use file_api::reader::Reader;
+use file_api::timeouts::{remaining_time, Timeouts};
+use file_api::MainReader;
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn stall_detection() {
+    let timeouts = Timeouts {
+        min_bytes_per_second: Some(1_000),
+        stall_window: Some(Duration::from_millis(50)),
+        ..Default::default()
+    };
+
+    let start = Instant::now();
+    assert!(timeouts.check_progress(start, 0).is_ok());
+
+    thread::sleep(Duration::from_millis(100));
+    match timeouts.check_progress(start, 10) {
+        Err(Error::Timeout(TimeoutKind::Stall)) => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(timeouts.check_progress(start, 1_000_000).is_ok());
+}
+
+#[test]
+fn expired_deadline() {
+    match remaining_time(Instant::now()) {
+        Err(Error::Timeout(TimeoutKind::Deadline)) => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(remaining_time(Instant::now() + Duration::from_secs(60)).is_ok());
+
+    let timeouts = Timeouts {
+        deadline: Some(Duration::from_secs(0)),
+        ..Default::default()
+    };
+    match http_reader::stat("http://127.0.0.1:1/file", &None, &timeouts) {
+        Err(ref error) if !error.is_retryable() => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn hung_server_times_out() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/file", server.local_addr().unwrap());
+    let handler = thread::spawn(move || {
+        let connection = server.accept().unwrap();
+        thread::sleep(Duration::from_secs(2));
+        drop(connection);
+    });
+
+    let timeouts = Timeouts {
+        read: Some(Duration::from_millis(200)),
+        ..Default::default()
+    };
+    let start = Instant::now();
+    match http_reader::stat(&url, &None, &timeouts) {
+        Err(Error::Timeout(TimeoutKind::Request)) => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    handler.join().unwrap();
+}
+
+/// Serve the `HEAD` request, then send the first 100 bytes of the body and
+/// keep the connection silent for a while.
+fn serve_stalled_body() -> (String, thread::JoinHandle<()>) {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/file", server.local_addr().unwrap());
+    let handler = thread::spawn(move || {
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        drop(connection);
+
+        let (mut connection, _) = accept(&server);
+        connection
+            .write_all(
+                b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-999/1000\r\n\
+                  Content-Length: 1000\r\nConnection: close\r\n\r\n",
+            )
+            .unwrap();
+        connection.write_all(&[0; 100]).unwrap();
+        thread::sleep(Duration::from_secs(2));
+    });
+    (url, handler)
+}
+
+fn assert_stalls(streaming: bool) {
+    let (url, handler) = serve_stalled_body();
+
+    let mut reader = MainReader::new();
+    reader.set_timeouts(Timeouts {
+        min_bytes_per_second: Some(1_000),
+        stall_window: Some(Duration::from_millis(200)),
+        ..Default::default()
+    });
+    reader.set_streaming(streaming);
+    reader.open(&url).unwrap();
+
+    let start = Instant::now();
+    let mut data = [0; 1000];
+    let error = reader.read_exact(&mut data).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::TimedOut);
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    handler.join().unwrap();
+}
+
+#[test]
+fn stalled_body() {
+    assert_stalls(false);
+}
+
+#[test]
+fn stalled_stream() {
+    assert_stalls(true);
+}
@@ -0,0 +1,127 @@
+extern crate file_api;
+
+mod common;
+
+use common::serve;
+use file_api::http_reader::HttpReader;
+use file_api::reader::Reader;
+use file_api::MainReader;
+
+use std::io::{BufRead, Read, Seek, SeekFrom};
+
+const HEAD_RESPONSE: &str = "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n";
+const NOT_SATISFIABLE_RESPONSE: &str = "HTTP/1.1 416 Range Not Satisfiable\r\n\
+                                        Content-Range: bytes */20\r\n\
+                                        Content-Length: 0\r\nConnection: close\r\n\r\n";
+
+fn partial_response(range: &str, length: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}/{}\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        range,
+        length,
+        body.len(),
+        body
+    )
+}
+
+#[test]
+fn range_not_satisfiable_is_eof() {
+    let (url, handler) = serve(vec![
+        HEAD_RESPONSE.to_string(),
+        partial_response("0-19", "*", "somedataandsomemore\n"),
+        NOT_SATISFIABLE_RESPONSE.to_string(),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    reader.set_cache_size(Some(32));
+
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"somedataandsomemore\n");
+    assert_eq!(reader.get_size().unwrap(), 20);
+    handler.join().unwrap();
+}
+
+#[test]
+fn lazy_size() {
+    let (url, handler) = serve(vec![
+        HEAD_RESPONSE.to_string(),
+        partial_response("0-0", "20", "s"),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    assert_eq!(reader.file_size, None);
+    assert_eq!(reader.get_size().unwrap(), 20);
+
+    let requests = handler.join().unwrap();
+    assert!(requests[1].contains("range: bytes=0-0"));
+}
+
+#[test]
+fn failed_size_discovery() {
+    let (url, handler) = serve(vec![
+        HEAD_RESPONSE.to_string(),
+        partial_response("0-0", "*", "s"),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    assert!(reader.get_size().is_err());
+    assert!(reader.get_size().is_err());
+    assert_eq!(reader.stats.requests, 2);
+    handler.join().unwrap();
+}
+
+#[test]
+fn size_discovery_retried_after_error() {
+    let (url, handler) = serve(vec![
+        HEAD_RESPONSE.to_string(),
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            .to_string(),
+        partial_response("0-0", "20", "s"),
+    ]);
+
+    let mut reader = HttpReader::new();
+    reader.open(&url).unwrap();
+    assert!(reader.get_size().is_err());
+    assert_eq!(reader.get_size().unwrap(), 20);
+    assert_eq!(reader.stats.requests, 3);
+    handler.join().unwrap();
+}
+
+#[test]
+fn streaming() {
+    let (url, handler) = serve(vec![
+        HEAD_RESPONSE.to_string(),
+        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+         8\r\nsomedata\r\nc\r\nandsomemore\n\r\n0\r\n\r\n"
+            .to_string(),
+        partial_response("8-19", "*", "andsomemore\n"),
+        NOT_SATISFIABLE_RESPONSE.to_string(),
+    ]);
+
+    let mut reader = MainReader::new();
+    reader.set_streaming(true);
+    reader.open(&url).unwrap();
+    assert!(reader.get_size().is_err());
+
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"somedataandsomemore\n");
+
+    reader.seek(SeekFrom::Start(8)).unwrap();
+    assert_eq!(reader.fill_buf().unwrap(), b"andsomemore\n");
+    reader.consume(12);
+    // The end of the body, then a new request refused with 416.
+    assert_eq!(reader.read(&mut [0; 4]).unwrap(), 0);
+    assert_eq!(reader.read(&mut [0; 4]).unwrap(), 0);
+    assert_eq!(reader.get_size().unwrap(), 20);
+
+    let requests = handler.join().unwrap();
+    assert!(requests[1].contains("range: bytes=0-"));
+    assert!(requests[2].contains("range: bytes=8-"));
+    assert!(requests[3].contains("range: bytes=20-"));
+}